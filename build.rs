@@ -32,6 +32,8 @@ fn main() {
             let val = key_and_val[1];
             if key.starts_with("FLAG") {
                 println!("cargo:rustc-cfg={}=\"{}\"", CFG_KEY, &key[5..])
+            } else if key.starts_with("CFG") {
+                println!("cargo:rustc-cfg={}", &key[4..])
             } else {
                 println!("cargo:rustc-cfg={}=\"{}_{}\"", CFG_KEY, &key[4..], val);
             }
@@ -307,7 +307,7 @@ print(sys.exec_prefix);";
 /// cargo vars to stdout.
 ///
 /// Note that if the python doesn't satisfy expected_version, this will error.
-fn configure_from_path(expected_version: &PythonVersion) -> Result<String, String> {
+fn configure_from_path(expected_version: &PythonVersion) -> Result<(String, PythonVersion), String> {
     let (interpreter_version, interpreter_path, lines) =
         find_interpreter_and_get_config(expected_version)?;
     let libpath: &str = &lines[0];
@@ -338,7 +338,7 @@ fn configure_from_path(expected_version: &PythonVersion) -> Result<String, Strin
         }
     }
 
-    return Ok(interpreter_path);
+    return Ok((interpreter_path, interpreter_version));
 }
 
 /// Determine the python version we're supposed to be building
@@ -381,7 +381,7 @@ fn main() {
     // try using 'env' (sorry but this isn't our fault - it just has to
     // match the pkg-config package name, which is going to have a . in it).
     let version = version_from_env().unwrap();
-    let python_interpreter_path = configure_from_path(&version).unwrap();
+    let (python_interpreter_path, interpreter_version) = configure_from_path(&version).unwrap();
     let mut config_map = get_config_vars(&python_interpreter_path).unwrap();
     if is_not_none_or_zero(config_map.get("Py_DEBUG")) {
         config_map.insert("Py_TRACE_REFS".to_owned(), "1".to_owned()); // Py_DEBUG implies Py_TRACE_REFS.
@@ -405,11 +405,12 @@ fn main() {
     //
     // FLAG indicates the variable is always 0 or 1
     // VAL indicates it can take on any value
+    // CFG indicates a plain cfg flag, such as the Py_3_x version flags
     //
     // rust-cypthon/build.rs contains an example of how to unpack this data
     // into cfg flags that replicate the ones present in this library, so
     // you can use the same cfg syntax.
-    let flags: String = config_map.iter().fold("".to_owned(), |memo, (key, val)| {
+    let mut flags: String = config_map.iter().fold("".to_owned(), |memo, (key, val)| {
         if is_value(key) {
             memo + format!("VAL_{}={},", key, val).as_ref()
         } else if val != "0" {
@@ -418,6 +419,11 @@ fn main() {
             memo
         }
     });
+    if let Some(minor) = interpreter_version.minor {
+        for i in 4..(minor+1) {
+            flags += format!("CFG_Py_3_{}=1,", i).as_ref();
+        }
+    }
     println!("cargo:python_flags={}",
         if flags.len() > 0 { &flags[..flags.len()-1] } else { "" });
 
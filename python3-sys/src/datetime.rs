@@ -0,0 +1,281 @@
+// Bindings for datetime.h, which is not included by Python.h.
+// The C API is provided by the datetime module through a capsule;
+// call `PyDateTime_IMPORT()` before using any of the functions in this module.
+
+use core::ptr;
+use libc::{c_char, c_int, c_uchar};
+use object::*;
+use pyport::Py_hash_t;
+use pycapsule::PyCapsule_Import;
+
+pub const _PyDateTime_DATE_DATASIZE: usize = 4;
+pub const _PyDateTime_TIME_DATASIZE: usize = 6;
+pub const _PyDateTime_DATETIME_DATASIZE: usize = 10;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Delta {
+    pub ob_base: PyObject,
+    pub hashcode: Py_hash_t,
+    pub days: c_int,
+    pub seconds: c_int,
+    pub microseconds: c_int,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_TZInfo {
+    pub ob_base: PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Date {
+    pub ob_base: PyObject,
+    pub hashcode: Py_hash_t,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_DATE_DATASIZE],
+}
+
+/// The `tzinfo` field is only present if `hastzinfo` is non-zero.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Time {
+    pub ob_base: PyObject,
+    pub hashcode: Py_hash_t,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_TIME_DATASIZE],
+    #[cfg(Py_3_6)]
+    pub fold: c_uchar,
+    pub tzinfo: *mut PyObject,
+}
+
+/// The `tzinfo` field is only present if `hastzinfo` is non-zero.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_DateTime {
+    pub ob_base: PyObject,
+    pub hashcode: Py_hash_t,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_DATETIME_DATASIZE],
+    #[cfg(Py_3_6)]
+    pub fold: c_uchar,
+    pub tzinfo: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_CAPI {
+    pub DateType: *mut PyTypeObject,
+    pub DateTimeType: *mut PyTypeObject,
+    pub TimeType: *mut PyTypeObject,
+    pub DeltaType: *mut PyTypeObject,
+    pub TZInfoType: *mut PyTypeObject,
+    #[cfg(Py_3_7)]
+    pub TimeZone_UTC: *mut PyObject,
+
+    pub Date_FromDate: unsafe extern "C" fn(year: c_int, month: c_int, day: c_int,
+                                            cls: *mut PyTypeObject) -> *mut PyObject,
+    pub DateTime_FromDateAndTime: unsafe extern "C" fn(year: c_int, month: c_int, day: c_int,
+                                                       hour: c_int, minute: c_int, second: c_int,
+                                                       usecond: c_int, tzinfo: *mut PyObject,
+                                                       cls: *mut PyTypeObject) -> *mut PyObject,
+    pub Time_FromTime: unsafe extern "C" fn(hour: c_int, minute: c_int, second: c_int,
+                                            usecond: c_int, tzinfo: *mut PyObject,
+                                            cls: *mut PyTypeObject) -> *mut PyObject,
+    pub Delta_FromDelta: unsafe extern "C" fn(days: c_int, seconds: c_int, useconds: c_int,
+                                              normalize: c_int,
+                                              cls: *mut PyTypeObject) -> *mut PyObject,
+    #[cfg(Py_3_7)]
+    pub TimeZone_FromTimeZone: unsafe extern "C" fn(offset: *mut PyObject,
+                                                    name: *mut PyObject) -> *mut PyObject,
+
+    pub DateTime_FromTimestamp: unsafe extern "C" fn(cls: *mut PyObject, args: *mut PyObject,
+                                                     kwargs: *mut PyObject) -> *mut PyObject,
+    pub Date_FromTimestamp: unsafe extern "C" fn(cls: *mut PyObject,
+                                                 args: *mut PyObject) -> *mut PyObject,
+
+    #[cfg(Py_3_6)]
+    pub DateTime_FromDateAndTimeAndFold: unsafe extern "C" fn(year: c_int, month: c_int, day: c_int,
+                                                              hour: c_int, minute: c_int, second: c_int,
+                                                              usecond: c_int, tzinfo: *mut PyObject,
+                                                              fold: c_int,
+                                                              cls: *mut PyTypeObject) -> *mut PyObject,
+    #[cfg(Py_3_6)]
+    pub Time_FromTimeAndFold: unsafe extern "C" fn(hour: c_int, minute: c_int, second: c_int,
+                                                   usecond: c_int, tzinfo: *mut PyObject,
+                                                   fold: c_int,
+                                                   cls: *mut PyTypeObject) -> *mut PyObject,
+}
+
+pub const PyDateTime_CAPSULE_NAME: &'static [u8] = b"datetime.datetime_CAPI\0";
+
+/// Pointer to the datetime C API; null until `PyDateTime_IMPORT()` succeeds.
+pub static mut PyDateTimeAPI: *mut PyDateTime_CAPI = ptr::null_mut();
+
+/// Imports the datetime C API into `PyDateTimeAPI`.
+/// On failure, `PyDateTimeAPI` remains null and a Python exception is set.
+#[inline]
+pub unsafe fn PyDateTime_IMPORT() {
+    PyDateTimeAPI = PyCapsule_Import(PyDateTime_CAPSULE_NAME.as_ptr() as *const c_char, 1)
+        as *mut PyDateTime_CAPI;
+}
+
+// Type checks
+
+#[inline]
+pub unsafe fn PyDate_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DateType)
+}
+
+#[inline]
+pub unsafe fn PyDate_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DateType) as c_int
+}
+
+#[inline]
+pub unsafe fn PyDateTime_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DateTimeType)
+}
+
+#[inline]
+pub unsafe fn PyDateTime_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DateTimeType) as c_int
+}
+
+#[inline]
+pub unsafe fn PyTime_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).TimeType)
+}
+
+#[inline]
+pub unsafe fn PyTime_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).TimeType) as c_int
+}
+
+#[inline]
+pub unsafe fn PyDelta_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DeltaType)
+}
+
+#[inline]
+pub unsafe fn PyDelta_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DeltaType) as c_int
+}
+
+#[inline]
+pub unsafe fn PyTZInfo_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).TZInfoType)
+}
+
+#[inline]
+pub unsafe fn PyTZInfo_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).TZInfoType) as c_int
+}
+
+// Accessors for date and datetime objects
+
+#[inline]
+pub unsafe fn PyDateTime_GET_YEAR(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_Date)).data;
+    (c_int::from(data[0]) << 8) | c_int::from(data[1])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_GET_MONTH(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Date)).data[2])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_GET_DAY(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Date)).data[3])
+}
+
+// Accessors for datetime objects
+
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_HOUR(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_DateTime)).data[4])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_MINUTE(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_DateTime)).data[5])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_SECOND(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_DateTime)).data[6])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_MICROSECOND(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_DateTime)).data;
+    (c_int::from(data[7]) << 16) | (c_int::from(data[8]) << 8) | c_int::from(data[9])
+}
+
+#[cfg(Py_3_6)]
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_FOLD(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_DateTime)).fold)
+}
+
+/// Returns a borrowed reference to the tzinfo, or `Py_None()` for naive datetimes.
+#[inline]
+pub unsafe fn PyDateTime_DATE_GET_TZINFO(o: *mut PyObject) -> *mut PyObject {
+    let dt = o as *mut PyDateTime_DateTime;
+    if (*dt).hastzinfo != 0 { (*dt).tzinfo } else { Py_None() }
+}
+
+// Accessors for time objects
+
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_HOUR(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Time)).data[0])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_MINUTE(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Time)).data[1])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_SECOND(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Time)).data[2])
+}
+
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_MICROSECOND(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_Time)).data;
+    (c_int::from(data[3]) << 16) | (c_int::from(data[4]) << 8) | c_int::from(data[5])
+}
+
+#[cfg(Py_3_6)]
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_FOLD(o: *mut PyObject) -> c_int {
+    c_int::from((*(o as *mut PyDateTime_Time)).fold)
+}
+
+/// Returns a borrowed reference to the tzinfo, or `Py_None()` for naive times.
+#[inline]
+pub unsafe fn PyDateTime_TIME_GET_TZINFO(o: *mut PyObject) -> *mut PyObject {
+    let t = o as *mut PyDateTime_Time;
+    if (*t).hastzinfo != 0 { (*t).tzinfo } else { Py_None() }
+}
+
+// Accessors for timedelta objects
+
+#[inline]
+pub unsafe fn PyDateTime_DELTA_GET_DAYS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).days
+}
+
+#[inline]
+pub unsafe fn PyDateTime_DELTA_GET_SECONDS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).seconds
+}
+
+#[inline]
+pub unsafe fn PyDateTime_DELTA_GET_MICROSECONDS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).microseconds
+}
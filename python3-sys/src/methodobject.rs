@@ -102,7 +102,10 @@ pub const METH_STATIC   : c_int = 0x0020;
 pub const METH_COEXIST   : c_int = 0x0040;
 
 #[cfg(all(Py_3_6, not(Py_LIMITED_API)))]
-pub const METHOD_FASTCALL : c_int = 0x0080;
+pub const METH_FASTCALL : c_int = 0x0080;
+
+#[cfg(all(Py_3_6, not(Py_LIMITED_API)))]
+pub const METHOD_FASTCALL : c_int = METH_FASTCALL;
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
     pub fn PyCFunction_ClearFreeList() -> c_int;
@@ -230,6 +230,12 @@ pub type allocfunc =
     unsafe extern "C" fn
                               (arg1: *mut PyTypeObject,
                                arg2: Py_ssize_t) -> *mut PyObject;
+#[cfg(Py_3_8)]
+pub type vectorcallfunc =
+    unsafe extern "C" fn
+                              (callable: *mut PyObject, args: *const *mut PyObject,
+                               nargsf: ::libc::size_t, kwnames: *mut PyObject)
+                              -> *mut PyObject;
 
 #[cfg(Py_LIMITED_API)]
 pub enum PyTypeObject { }
@@ -436,7 +442,10 @@ mod typeobject {
         pub tp_basicsize: Py_ssize_t,
         pub tp_itemsize: Py_ssize_t,
         pub tp_dealloc: Option<::object::destructor>,
+        #[cfg(not(Py_3_8))]
         pub tp_print: Option<::object::printfunc>,
+        #[cfg(Py_3_8)]
+        pub tp_vectorcall_offset: Py_ssize_t,
         pub tp_getattr: Option<::object::getattrfunc>,
         pub tp_setattr: Option<::object::setattrfunc>,
         #[cfg(Py_3_5)]
@@ -483,6 +492,10 @@ mod typeobject {
         pub tp_version_tag: c_uint,
         #[cfg(Py_3_4)]
         pub tp_finalize: Option<::object::destructor>,
+        #[cfg(Py_3_8)]
+        pub tp_vectorcall: Option<::object::vectorcallfunc>,
+        #[cfg(all(Py_3_8, not(Py_3_9)))]
+        pub tp_print: Option<::object::printfunc>,
         #[cfg(py_sys_config="COUNT_ALLOCS")]
         pub tp_allocs: Py_ssize_t,
         #[cfg(py_sys_config="COUNT_ALLOCS")]
@@ -510,7 +523,10 @@ mod typeobject {
                     tp_basicsize: 0,
                     tp_itemsize: 0,
                     tp_dealloc: None,
+                    #[cfg(not(Py_3_8))]
                     tp_print: None,
+                    #[cfg(Py_3_8)]
+                    tp_vectorcall_offset: 0,
                     tp_getattr: None,
                     tp_setattr: None,
                     $tp_as_async: 0 as *mut _,
@@ -579,7 +595,22 @@ mod typeobject {
         }
     }
 
-    #[cfg(Py_3_5)]
+    #[cfg(Py_3_9)]
+    pub const PyTypeObject_INIT: PyTypeObject = py_type_object_init_with_count_allocs!(
+        tp_as_async,
+        tp_finalize: None,
+        tp_vectorcall: None,
+    );
+
+    #[cfg(all(Py_3_8, not(Py_3_9)))]
+    pub const PyTypeObject_INIT: PyTypeObject = py_type_object_init_with_count_allocs!(
+        tp_as_async,
+        tp_finalize: None,
+        tp_vectorcall: None,
+        tp_print: None,
+    );
+
+    #[cfg(all(Py_3_5, not(Py_3_8)))]
     pub const PyTypeObject_INIT: PyTypeObject = py_type_object_init_with_count_allocs!(
         tp_as_async,
         tp_finalize: None,
@@ -779,6 +810,12 @@ pub const Py_TPFLAGS_HEAPTYPE : c_ulong = (1<<9);
 /// Set if the type allows subclassing
 pub const Py_TPFLAGS_BASETYPE : c_ulong = (1<<10);
 
+/// Set if the type implements the vectorcall protocol (PEP 590)
+#[cfg(Py_3_8)]
+pub const Py_TPFLAGS_HAVE_VECTORCALL : c_ulong = (1<<11);
+#[cfg(Py_3_8)]
+pub const _Py_TPFLAGS_HAVE_VECTORCALL : c_ulong = Py_TPFLAGS_HAVE_VECTORCALL;
+
 /// Set if the type is 'ready' -- fully initialized
 pub const Py_TPFLAGS_READY : c_ulong = (1<<12);
 
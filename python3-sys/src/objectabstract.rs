@@ -1,7 +1,11 @@
-use libc::{c_void, c_char, c_int};
+use libc::{c_void, c_char, c_int, size_t};
 use pyport::Py_ssize_t;
 use core::ptr;
 use object::*;
+#[cfg(Py_3_9)]
+use pystate::PyThreadState;
+#[cfg(all(Py_3_9, not(Py_3_11)))]
+use pystate::PyThreadState_Get;
 
 #[inline]
 pub unsafe fn PyObject_DelAttrString(o: *mut PyObject, attr_name: *const c_char) -> c_int {
@@ -36,6 +40,100 @@ pub unsafe fn PyObject_DelAttr(o: *mut PyObject, attr_name: *mut PyObject) -> c_
     pub fn PyObject_Size(o: *mut PyObject) -> Py_ssize_t;
 }
 
+// Vectorcall protocol (PEP 590)
+
+#[cfg(Py_3_8)]
+pub const PY_VECTORCALL_ARGUMENTS_OFFSET: size_t = 1 << (8 * ::core::mem::size_of::<size_t>() - 1);
+
+#[cfg(Py_3_8)]
+#[inline]
+pub unsafe fn PyVectorcall_NARGS(n: size_t) -> Py_ssize_t {
+    (n & !PY_VECTORCALL_ARGUMENTS_OFFSET) as Py_ssize_t
+}
+
+#[cfg(all(Py_3_8, not(Py_3_9)))]
+#[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
+    pub fn _Py_CheckFunctionResult(callable: *mut PyObject, result: *mut PyObject,
+                                   where_: *const c_char) -> *mut PyObject;
+    pub fn _PyObject_MakeTpCall(callable: *mut PyObject, args: *const *mut PyObject,
+                                nargs: Py_ssize_t, keywords: *mut PyObject) -> *mut PyObject;
+}
+
+#[cfg(Py_3_9)]
+#[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
+    pub fn _Py_CheckFunctionResult(tstate: *mut PyThreadState, callable: *mut PyObject,
+                                   result: *mut PyObject, where_: *const c_char) -> *mut PyObject;
+    pub fn _PyObject_MakeTpCall(tstate: *mut PyThreadState, callable: *mut PyObject,
+                                args: *const *mut PyObject, nargs: Py_ssize_t,
+                                keywords: *mut PyObject) -> *mut PyObject;
+    pub fn PyObject_VectorcallDict(callable: *mut PyObject, args: *const *mut PyObject,
+                                   nargsf: size_t, kwdict: *mut PyObject) -> *mut PyObject;
+    pub fn PyObject_VectorcallMethod(name: *mut PyObject, args: *const *mut PyObject,
+                                     nargsf: size_t, kwnames: *mut PyObject) -> *mut PyObject;
+}
+
+#[cfg(Py_3_8)]
+#[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
+    pub fn PyVectorcall_Call(callable: *mut PyObject, tuple: *mut PyObject,
+                             dict: *mut PyObject) -> *mut PyObject;
+}
+
+#[cfg(Py_3_11)]
+#[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
+    pub fn PyVectorcall_Function(callable: *mut PyObject) -> Option<vectorcallfunc>;
+    pub fn PyObject_Vectorcall(callable: *mut PyObject, args: *const *mut PyObject,
+                               nargsf: size_t, kwnames: *mut PyObject) -> *mut PyObject;
+}
+
+// Before Python 3.11, these were static inline functions in the headers.
+
+#[cfg(all(Py_3_8, not(Py_3_11)))]
+#[inline]
+pub unsafe fn PyVectorcall_Function(callable: *mut PyObject) -> Option<vectorcallfunc> {
+    let tp = Py_TYPE(callable);
+    if PyType_HasFeature(tp, Py_TPFLAGS_HAVE_VECTORCALL) == 0 {
+        return None;
+    }
+    let offset = (*tp).tp_vectorcall_offset;
+    debug_assert!(offset > 0);
+    *((callable as *mut u8).offset(offset) as *mut Option<vectorcallfunc>)
+}
+
+#[cfg(all(Py_3_8, not(Py_3_9)))]
+#[inline]
+pub unsafe fn PyObject_Vectorcall(callable: *mut PyObject, args: *const *mut PyObject,
+                                  nargsf: size_t, kwnames: *mut PyObject) -> *mut PyObject {
+    match PyVectorcall_Function(callable) {
+        Some(func) => {
+            let res = func(callable, args, nargsf, kwnames);
+            _Py_CheckFunctionResult(callable, res, ptr::null())
+        }
+        None => _PyObject_MakeTpCall(callable, args, PyVectorcall_NARGS(nargsf), kwnames)
+    }
+}
+
+#[cfg(all(Py_3_9, not(Py_3_11)))]
+#[inline]
+pub unsafe fn PyObject_Vectorcall(callable: *mut PyObject, args: *const *mut PyObject,
+                                  nargsf: size_t, kwnames: *mut PyObject) -> *mut PyObject {
+    let tstate = PyThreadState_Get();
+    match PyVectorcall_Function(callable) {
+        Some(func) => {
+            let res = func(callable, args, nargsf, kwnames);
+            _Py_CheckFunctionResult(tstate, callable, res, ptr::null())
+        }
+        None => _PyObject_MakeTpCall(tstate, callable, args, PyVectorcall_NARGS(nargsf), kwnames)
+    }
+}
+
+/// Python 3.8 name of `PyObject_Vectorcall`.
+#[cfg(Py_3_8)]
+#[inline]
+pub unsafe fn _PyObject_Vectorcall(callable: *mut PyObject, args: *const *mut PyObject,
+                                   nargsf: size_t, kwnames: *mut PyObject) -> *mut PyObject {
+    PyObject_Vectorcall(callable, args, nargsf, kwnames)
+}
+
 #[inline]
 pub unsafe fn PyObject_Length(o: *mut PyObject) -> Py_ssize_t {
     PyObject_Size(o)
@@ -19,19 +19,36 @@
 //! This module contains logic for parsing a python argument list.
 //! See also the macros `py_argparse!`, `py_fn!` and `py_method!`.
 
-use std::ptr;
-use python::{Python, PythonObject};
+use std::{ptr, slice};
+use std::marker::PhantomData;
+use python::{Python, PythonObject, PythonObjectWithTypeObject, PyClone};
 use objects::{PyObject, PyTuple, PyDict, PyString, exc};
 use conversion::{RefFromPyObject, ToPyObject};
 use ffi;
-use err::{self, PyResult};
+use err::{self, PyErr, PyResult};
 
 /// Description of a python parameter; used for `parse_args()`.
 pub struct ParamDescription<'a> {
     /// The name of the parameter.
     pub name: &'a str,
     /// Whether the parameter is optional.
-    pub is_optional: bool
+    pub is_optional: bool,
+    /// Whether the parameter can only be passed by position (declared before `/`).
+    pub is_positional_only: bool,
+    /// Whether the parameter can only be passed by keyword (declared after `*`).
+    pub is_keyword_only: bool,
+}
+
+impl <'a> ParamDescription<'a> {
+    /// Creates the description of a parameter that can be passed by position or by keyword.
+    pub fn new(name: &'a str, is_optional: bool) -> ParamDescription<'a> {
+        ParamDescription {
+            name: name,
+            is_optional: is_optional,
+            is_positional_only: false,
+            is_keyword_only: false,
+        }
+    }
 }
 
 /// Parse argument list
@@ -48,61 +65,128 @@ pub fn parse_args(
     args: &PyTuple, kwargs: Option<&PyDict>,
     output: &mut[Option<PyObject>]
 ) -> PyResult<()>
+{
+    match kwargs {
+        Some(kwargs) => {
+            let items = kwargs.items(py);
+            parse_args_impl(py, fname, params, args.as_slice(py),
+                items.iter().map(|&(ref key, ref value)| (key, value)), output)
+        }
+        None => parse_args_impl(py, fname, params, args.as_slice(py), None.into_iter(), output)
+    }
+}
+
+/// Parse argument list passed using the vectorcall / `METH_FASTCALL` calling convention
+///
+///  * fname:   Name of the current function
+///  * params:  Declared parameters of the function
+///  * args:    Positional arguments, followed by the values of the keyword arguments
+///  * kwnames: Names of the keyword arguments
+///  * output:  Output array that receives the arguments.
+///            Must have same length as `params` and must be initialized to `None`.
+pub fn parse_fastcall_args(
+    py: Python,
+    fname: Option<&str>, params: &[ParamDescription],
+    args: &[PyObject], kwnames: Option<&PyTuple>,
+    output: &mut[Option<PyObject>]
+) -> PyResult<()>
+{
+    match kwnames {
+        Some(kwnames) => {
+            let kwnames = kwnames.as_slice(py);
+            assert!(kwnames.len() <= args.len());
+            let (args, kwvalues) = args.split_at(args.len() - kwnames.len());
+            parse_args_impl(py, fname, params, args, kwnames.iter().zip(kwvalues), output)
+        }
+        None => parse_args_impl(py, fname, params, args, None.into_iter(), output)
+    }
+}
+
+fn parse_args_impl<'a, I>(
+    py: Python,
+    fname: Option<&str>, params: &[ParamDescription],
+    args: &[PyObject], kwargs: I,
+    output: &mut[Option<PyObject>]
+) -> PyResult<()>
+    where I: Iterator<Item=(&'a PyObject, &'a PyObject)>
 {
     assert!(params.len() == output.len());
-    let nargs = args.len(py);
-    let nkeywords = kwargs.map_or(0, |d| d.len(py));
-    if nargs + nkeywords > params.len() {
+    let fname = match fname {
+        Some(fname) => format!("{}()", fname),
+        None => "function".to_owned()
+    };
+    let nargs = args.len();
+    let npositional = params.iter().filter(|p| !p.is_keyword_only).count();
+    if nargs > npositional {
+        let nrequired = params.iter().filter(|p| !p.is_keyword_only && !p.is_optional).count();
         return Err(err::PyErr::new::<exc::TypeError, _>(py,
-            format!("{}{} takes at most {} argument{} ({} given)",
-                    fname.unwrap_or("function"),
-                    if fname.is_some() { "()" } else { "" },
-                    params.len(),
-                    if params.len() == 1 { "s" } else { "" },
-                    nargs + nkeywords
-                )));
+            if nrequired == npositional {
+                format!("{} takes {} positional argument{} but {} {} given",
+                        fname, npositional, if npositional == 1 { "" } else { "s" },
+                        nargs, if nargs == 1 { "was" } else { "were" })
+            } else {
+                format!("{} takes from {} to {} positional arguments but {} {} given",
+                        fname, nrequired, npositional,
+                        nargs, if nargs == 1 { "was" } else { "were" })
+            }));
+    }
+    for (out, arg) in output.iter_mut().zip(args) {
+        *out = Some(arg.clone_ref(py));
     }
-    let mut used_keywords = 0;
-    // Iterate through the parameters and assign values to output:
-    for (i, (p, out)) in params.iter().zip(output).enumerate() {
-        match kwargs.and_then(|d| d.get_item(py, p.name)) {
-            Some(kwarg) => {
-                *out = Some(kwarg);
-                used_keywords += 1;
-                if i < nargs {
+    let mut positional_only_passed_as_keyword = Vec::new();
+    for (key, value) in kwargs {
+        let key = key.cast_as::<PyString>(py)?.to_string(py)?;
+        match params.iter().position(|p| p.name == key && !p.is_positional_only) {
+            Some(i) => {
+                if output[i].is_some() {
                     return Err(err::PyErr::new::<exc::TypeError, _>(py,
-                        format!("Argument given by name ('{}') and position ({})",
-                                p.name, i+1)));
+                        format!("{} got multiple values for argument '{}'", fname, key)));
                 }
+                output[i] = Some(value.clone_ref(py));
             },
             None => {
-                if i < nargs {
-                    *out = Some(args.get_item(py, i));
+                if params.iter().any(|p| p.name == key) {
+                    positional_only_passed_as_keyword.push(key.into_owned());
                 } else {
-                    *out = None;
-                    if !p.is_optional {
-                        return Err(err::PyErr::new::<exc::TypeError, _>(py,
-                            format!("Required argument ('{}') (pos {}) not found",
-                                    p.name, i+1)));
-                    }
+                    return Err(err::PyErr::new::<exc::TypeError, _>(py,
+                        format!("{} got an unexpected keyword argument '{}'", fname, key)));
                 }
             }
         }
     }
-    if used_keywords != nkeywords {
-        // check for extraneous keyword arguments
-        for (key, _value) in kwargs.unwrap().items(py) {
-            let key = key.cast_as::<PyString>(py)?.to_string(py)?;
-            if !params.iter().any(|p| p.name == key) {
-                return Err(err::PyErr::new::<exc::TypeError, _>(py,
-                    format!("'{}' is an invalid keyword argument for this function",
-                            key)));
-            }
+    if !positional_only_passed_as_keyword.is_empty() {
+        return Err(err::PyErr::new::<exc::TypeError, _>(py,
+            format!("{} got some positional-only arguments passed as keyword arguments: '{}'",
+                    fname, positional_only_passed_as_keyword.join(", "))));
+    }
+    for &keyword_only in &[false, true] {
+        let missing: Vec<&str> = params.iter().zip(output.iter())
+            .filter(|&(p, out)| p.is_keyword_only == keyword_only && !p.is_optional && out.is_none())
+            .map(|(p, _)| p.name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(err::PyErr::new::<exc::TypeError, _>(py,
+                format!("{} missing {} required {} argument{}: {}",
+                        fname, missing.len(),
+                        if keyword_only { "keyword-only" } else { "positional" },
+                        if missing.len() == 1 { "" } else { "s" },
+                        format_missing_names(&missing))));
         }
     }
     Ok(())
 }
 
+/// Formats a list of parameter names like CPython: `'a'`, `'a' and 'b'`, `'a', 'b', and 'c'`
+fn format_missing_names(names: &[&str]) -> String {
+    let quoted: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+    match quoted.len() {
+        0 => String::new(),
+        1 => quoted[0].clone(),
+        2 => format!("{} and {}", quoted[0], quoted[1]),
+        n => format!("{}, and {}", quoted[..n-1].join(", "), quoted[n-1])
+    }
+}
+
 /// This macro is used to parse a parameter list into a set of variables.
 ///
 /// Syntax: `py_argparse!(py, fname, args, kwargs, (parameter-list) { body })`
@@ -126,6 +210,10 @@ pub fn parse_args(
 ///   `&PyObject` (format 1), `&PyTuple` (format 4) or `&PyDict` (format 6).
 ///   If a default value is specified, it must be a compile-time constant
 //    of type `ty`.
+///
+///   Like in Python, the parameter list may also contain the markers `/` and `*`:
+///   parameters before `/` are positional-only, and parameters after `*` are keyword-only.
+///   For example, `(a: i32, /, b: i32, *, c: i32)` corresponds to `def f(a, /, b, *, c)`.
 ///  * `body`: expression of type `PyResult<_>`.
 ///     The extracted argument values are available in this scope.
 ///
@@ -176,6 +264,29 @@ macro_rules! py_argparse_parse_plist_impl {
     { $callback:ident { $($initial_arg:tt)* } $output:tt ( ) } => {
         $callback! { $($initial_arg)* $output }
     };
+    // Positional-only marker: all preceding parameters are positional-only
+    { $callback:ident $initial_args:tt
+        [ $( { $pname:ident : $ptype:ty = [ {} $default:tt $rtype:tt ] } )* ]
+        ( / , $($tail:tt)* )
+    } => {
+        py_argparse_parse_plist_impl! {
+            $callback $initial_args
+            [ $( { $pname:$ptype = [ {/} $default $rtype ] } )* ]
+            ($($tail)*)
+        }
+    };
+    // Keyword-only marker: all following parameters are keyword-only.
+    // The remaining parameters are parsed separately and then passed to
+    // py_argparse_parse_plist_kwonly!, which marks them as keyword-only.
+    { $callback:ident $initial_args:tt $output:tt
+        ( * , $($tail:tt)+ )
+    } => {
+        py_argparse_parse_plist_impl! {
+            py_argparse_parse_plist_kwonly { $callback $initial_args $output }
+            []
+            ($($tail)+)
+        }
+    };
     // Kwargs parameter with reference extraction
     { $callback:ident $initial_args:tt [ $($output:tt)* ]
         ( ** $name:ident : &$t:ty , $($tail:tt)* )
@@ -288,6 +399,39 @@ macro_rules! py_argparse_parse_plist_impl {
     };
 }
 
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_argparse_parse_plist_kwonly {
+    // Helper for py_argparse_parse_plist_impl!: appends the parameters following
+    // a `*` marker to the output, marking them as keyword-only.
+    // py_argparse_parse_plist_kwonly! { callback initial_args [output] [parameters] }
+
+    // Base case: all parameters handled
+    { $callback:ident { $($initial_arg:tt)* } [ $($output:tt)* ] [ ] } => {
+        $callback! { $($initial_arg)* [ $($output)* ] }
+    };
+    // Normal or optional parameter
+    { $callback:ident $initial_args:tt [ $($output:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {} $default:tt $rtype:tt ] } $($tail:tt)* ]
+    } => {
+        py_argparse_parse_plist_kwonly! {
+            $callback $initial_args
+            [ $($output)* { $pname:$ptype = [ {*,} $default $rtype ] } ]
+            [ $($tail)* ]
+        }
+    };
+    // Kwargs parameter
+    { $callback:ident $initial_args:tt [ $($output:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {**} {} $rtype:tt ] } ]
+    } => {
+        py_argparse_parse_plist_kwonly! {
+            $callback $initial_args
+            [ $($output)* { $pname:$ptype = [ {**} {} $rtype ] } ]
+            [ ]
+        }
+    };
+}
+
 // The main py_argparse!() macro, except that it expects the parameter-list
 // in the output format of py_argparse_parse_plist!().
 #[macro_export(local_inner_macros)]
@@ -318,8 +462,9 @@ macro_rules! py_argparse_impl {
             ),*
         ];
         let py: $crate::Python = $py;
+        let fname: Option<&str> = $fname;
         let mut output = [$( py_replace_expr!($pname None) ),*];
-        match $crate::argparse::parse_args(py, $fname, PARAMS, $args, $kwargs, &mut output) {
+        match $crate::argparse::parse_args(py, fname, PARAMS, $args, $kwargs, &mut output) {
             Ok(()) => {
                 // Experimental slice pattern syntax would be really nice here (#23121)
                 //let [$(ref $pname),*] = output;
@@ -328,7 +473,7 @@ macro_rules! py_argparse_impl {
                 // We'll have to generate a bunch of nested `match` statements
                 // (at least until we can use ? + catch, assuming that will be hygienic wrt. macros),
                 // so use a recursive helper macro for that:
-                py_argparse_extract!( py, _iter, $body,
+                py_argparse_extract!( py, fname, _iter, $body,
                     [ $( { $pname : $ptype = $detail } )* ])
             },
             Err(e) => Err(e)
@@ -350,6 +495,59 @@ macro_rules! py_argparse_raw {
     }};
 }
 
+// Like py_argparse_raw!(), but accepts the arguments in the `METH_FASTCALL | METH_KEYWORDS`
+// calling convention: `$args: *const *mut ffi::PyObject`, `$nargs: Py_ssize_t` and
+// `$kwnames: *mut ffi::PyObject`.
+// Does not support `*args` or `**kwargs` parameters.
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_argparse_fastcall_raw {
+    ($py:ident, $fname:expr, $args:expr, $nargs:expr, $kwnames:expr,
+        [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] $body:block
+    ) => {{
+        const PARAMS: &'static [$crate::argparse::ParamDescription<'static>] = &[
+            $(
+                py_argparse_param_description! { $pname : $ptype = $detail }
+            ),*
+        ];
+        let kwnames: Option<&$crate::PyTuple> = $crate::argparse::get_kwnames(&$kwnames);
+        let args: &[$crate::PyObject] = $crate::argparse::get_fastcall_args($py, $args, $nargs, kwnames);
+        let fname: Option<&str> = $fname;
+        let mut output = [$( py_replace_expr!($pname None) ),*];
+        match $crate::argparse::parse_fastcall_args($py, fname, PARAMS, args, kwnames, &mut output) {
+            Ok(()) => {
+                let mut _iter = output.iter();
+                py_argparse_extract!( $py, fname, _iter, $body,
+                    [ $( { $pname : $ptype = $detail } )* ])
+            },
+            Err(e) => Err(e)
+        }
+    }};
+}
+
+#[inline]
+#[doc(hidden)]
+pub unsafe fn get_kwnames(ptr: &*mut ffi::PyObject) -> Option<&PyTuple> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(PyObject::borrow_from_ptr(ptr).unchecked_cast_as())
+    }
+}
+
+#[inline]
+#[doc(hidden)]
+pub unsafe fn get_fastcall_args<'a>(py: Python, args: *const *mut ffi::PyObject,
+    nargs: ffi::Py_ssize_t, kwnames: Option<&PyTuple>) -> &'a [PyObject]
+{
+    let len = nargs as usize + kwnames.map_or(0, |t| t.len(py));
+    if len == 0 {
+        &[]
+    } else {
+        PyObject::borrow_from_owned_ptr_slice(slice::from_raw_parts(args, len))
+    }
+}
+
 #[inline]
 #[doc(hidden)]
 pub unsafe fn get_kwargs(py: Python, ptr: *mut ffi::PyObject) -> Option<PyDict> {
@@ -367,14 +565,54 @@ macro_rules! py_argparse_param_description {
     { $pname:ident : $ptype:ty = [ {} {} $rtype:tt ] } => (
         $crate::argparse::ParamDescription {
             name: stringify!($pname),
-            is_optional: false
+            is_optional: false,
+            is_positional_only: false,
+            is_keyword_only: false,
         }
     );
     // optional parameters
     { $pname:ident : $ptype:ty = [ {} {$default:expr} {$($rtype:tt)*} ] } => (
         $crate::argparse::ParamDescription {
             name: stringify!($pname),
-            is_optional: true
+            is_optional: true,
+            is_positional_only: false,
+            is_keyword_only: false,
+        }
+    );
+    // positional-only parameter
+    { $pname:ident : $ptype:ty = [ {/} {} $rtype:tt ] } => (
+        $crate::argparse::ParamDescription {
+            name: stringify!($pname),
+            is_optional: false,
+            is_positional_only: true,
+            is_keyword_only: false,
+        }
+    );
+    // optional positional-only parameter
+    { $pname:ident : $ptype:ty = [ {/} {$default:expr} {$($rtype:tt)*} ] } => (
+        $crate::argparse::ParamDescription {
+            name: stringify!($pname),
+            is_optional: true,
+            is_positional_only: true,
+            is_keyword_only: false,
+        }
+    );
+    // keyword-only parameter
+    { $pname:ident : $ptype:ty = [ {*,} {} $rtype:tt ] } => (
+        $crate::argparse::ParamDescription {
+            name: stringify!($pname),
+            is_optional: false,
+            is_positional_only: false,
+            is_keyword_only: true,
+        }
+    );
+    // optional keyword-only parameter
+    { $pname:ident : $ptype:ty = [ {*,} {$default:expr} {$($rtype:tt)*} ] } => (
+        $crate::argparse::ParamDescription {
+            name: stringify!($pname),
+            is_optional: true,
+            is_positional_only: false,
+            is_keyword_only: true,
         }
     );
 }
@@ -383,72 +621,216 @@ macro_rules! py_argparse_param_description {
 #[doc(hidden)]
 macro_rules! py_argparse_extract {
     // base case
-    ( $py:expr, $iter:expr, $body:block, [] ) => { $body };
+    ( $py:expr, $fname:expr, $iter:expr, $body:block, [] ) => { $body };
     // normal parameter
-    ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {} {} ] } $($tail:tt)* ]
-    ) => {
+    ( $py:expr, $fname:expr, $iter:expr, $body:block,
+        [ { $pname:ident : $ptype:ty = [ $kind:tt {} {} ] } $($tail:tt)* ]
+    ) => {{
         // First unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         // second unwrap() asserts the parameter was not missing (which fn parse_args already checked for).
-        match <$ptype as $crate::FromPyObject>::extract($py, $iter.next().unwrap().as_ref().unwrap()) {
-            Ok($pname) => py_argparse_extract!($py, $iter, $body, [$($tail)*]),
-            Err(e) => Err(e)
+        let obj = $iter.next().unwrap().as_ref().unwrap();
+        match <$ptype as $crate::FromPyObject>::extract($py, obj) {
+            Ok($pname) => py_argparse_extract!($py, $fname, $iter, $body, [$($tail)*]),
+            Err(e) => Err($crate::argparse::argument_extraction_error($py, $fname,
+                _cpython__argparse__stringify!($pname), &py_argparse_expected_type!($py, $ptype), obj, e))
         }
-    };
+    }};
     // normal parameter with reference extraction
-    ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {} {$rtype:ty} ] } $($tail:tt)* ]
-    ) => {
+    ( $py:expr, $fname:expr, $iter:expr, $body:block,
+        [ { $pname:ident : $ptype:ty = [ $kind:tt {} {$rtype:ty} ] } $($tail:tt)* ]
+    ) => {{
         // First unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         // second unwrap() asserts the parameter was not missing (which fn parse_args already checked for).
-        match <$rtype as $crate::RefFromPyObject>::with_extracted($py,
-            $iter.next().unwrap().as_ref().unwrap(),
-            |$pname: $ptype| py_argparse_extract!($py, $iter, $body, [$($tail)*])
+        let obj = $iter.next().unwrap().as_ref().unwrap();
+        match <$rtype as $crate::RefFromPyObject>::with_extracted($py, obj,
+            |$pname: $ptype| py_argparse_extract!($py, $fname, $iter, $body, [$($tail)*])
         ) {
             Ok(v) => v,
-            Err(e) => Err(e)
+            Err(e) => Err($crate::argparse::argument_extraction_error($py, $fname,
+                _cpython__argparse__stringify!($pname), &py_argparse_expected_type!($py, $rtype), obj, e))
         }
-    };
+    }};
     // optional parameter
-    ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {$default:expr} {} ] } $($tail:tt)* ]
+    ( $py:expr, $fname:expr, $iter:expr, $body:block,
+        [ { $pname:ident : $ptype:ty = [ $kind:tt {$default:expr} {} ] } $($tail:tt)* ]
     ) => {
-        match $iter.next().unwrap().as_ref().map(|obj| obj.extract::<_>($py)).unwrap_or(Ok($default)) {
-            Ok($pname) => py_argparse_extract!($py, $iter, $body, [$($tail)*]),
+        match $iter.next().unwrap().as_ref()
+            .map(|obj| obj.extract::<$ptype>($py).map_err(|e| $crate::argparse::argument_extraction_error($py, $fname,
+                _cpython__argparse__stringify!($pname), &py_argparse_expected_type!($py, $ptype), obj, e)))
+            .unwrap_or(Ok($default))
+        {
+            Ok($pname) => py_argparse_extract!($py, $fname, $iter, $body, [$($tail)*]),
             Err(e) => Err(e)
         }
     };
     // optional parameter with reference extraction
-    ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {$default:expr} {$rtype:ty} ] } $($tail:tt)* ]
+    ( $py:expr, $fname:expr, $iter:expr, $body:block,
+        [ { $pname:ident : $ptype:ty = [ $kind:tt {$default:expr} {$rtype:ty} ] } $($tail:tt)* ]
     ) => {
         //unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         $crate::argparse::with_extracted_or_default($py,
             $iter.next().unwrap().as_ref(),
-            |$pname: $ptype| py_argparse_extract!($py, $iter, $body, [$($tail)*]),
-            $default)
+            |$pname: $ptype| py_argparse_extract!($py, $fname, $iter, $body, [$($tail)*]),
+            $default,
+            |obj, e| $crate::argparse::argument_extraction_error($py, $fname,
+                _cpython__argparse__stringify!($pname), &py_argparse_expected_type!($py, $rtype), obj, e))
     };
 }
 
+// Gets the name of the Python type expected for a parameter of type `$ptype`.
+// Uses the type object if `$ptype` implements `PythonObjectWithTypeObject`,
+// and falls back to `expected_type_name()` otherwise.
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_argparse_expected_type {
+    ($py:expr, $ptype:ty) => {{
+        #[allow(unused_imports)]
+        use $crate::argparse::{ExpectedTypeObject, ExpectedTypeName};
+        (&$crate::argparse::ExpectedType::<$ptype>(::std::marker::PhantomData))
+            .expected_type_name($py, _cpython__argparse__stringify!($ptype))
+    }};
+}
+
+#[doc(hidden)] // used in py_argparse_expected_type!() macro
+pub struct ExpectedType<T: ?Sized>(pub PhantomData<T>);
+
+// Implemented for `ExpectedType<T>`, so method resolution picks it over
+// `ExpectedTypeName` (implemented for `&ExpectedType<T>`) whenever it applies.
+#[doc(hidden)] // used in py_argparse_expected_type!() macro
+pub trait ExpectedTypeObject {
+    fn expected_type_name(&self, py: Python, ptype: &str) -> String;
+}
+
+impl <T> ExpectedTypeObject for ExpectedType<T> where T: PythonObjectWithTypeObject {
+    fn expected_type_name(&self, py: Python, _ptype: &str) -> String {
+        T::type_object(py).name(py).into_owned()
+    }
+}
+
+impl <T> ExpectedTypeObject for ExpectedType<Option<T>> where T: PythonObjectWithTypeObject {
+    fn expected_type_name(&self, py: Python, _ptype: &str) -> String {
+        format!("{} or None", T::type_object(py).name(py))
+    }
+}
+
+#[doc(hidden)] // used in py_argparse_expected_type!() macro
+pub trait ExpectedTypeName {
+    fn expected_type_name(&self, py: Python, ptype: &str) -> String;
+}
+
+impl <'a, T: ?Sized> ExpectedTypeName for &'a ExpectedType<T> {
+    fn expected_type_name(&self, _py: Python, ptype: &str) -> String {
+        expected_type_name(ptype)
+    }
+}
+
 #[doc(hidden)] // used in py_argparse_extract!() macro
-pub fn with_extracted_or_default<P: ?Sized, R, F>(py: Python, obj: Option<&PyObject>, f: F, default: &'static P) -> PyResult<R>
+pub fn with_extracted_or_default<P: ?Sized, R, F, E>(py: Python, obj: Option<&PyObject>, f: F, default: &'static P, on_error: E) -> PyResult<R>
     where F: FnOnce(&P) -> PyResult<R>,
+          E: FnOnce(&PyObject, PyErr) -> PyErr,
           P: RefFromPyObject
 {
     match obj {
         Some(obj) => match P::with_extracted(py, obj, f) {
             Ok(result) => result,
-            Err(e) => Err(e)
+            Err(e) => Err(on_error(obj, e))
         },
         None => f(default)
     }
 }
 
+/// Wraps a `TypeError` that occurred while converting the argument `obj`
+/// for the parameter `pname` of function `fname`.
+///
+/// The new `TypeError` names the parameter and the expected type,
+/// e.g. `f() argument 'count': expected int, got str`.
+/// On Python 3, the original exception is kept as `__cause__`.
+/// Other exception types (e.g. `OverflowError`) are returned unchanged.
+#[doc(hidden)] // used in py_argparse_extract!() macro
+pub fn argument_extraction_error(
+    py: Python, fname: Option<&str>, pname: &str, expected: &str,
+    obj: &PyObject, mut err: PyErr
+) -> PyErr {
+    if !err.matches(py, py.get_type::<exc::TypeError>()) {
+        return err;
+    }
+    let fname = match fname {
+        Some(fname) => format!("{}()", fname),
+        None => "function".to_owned()
+    };
+    let msg = format!("{} argument '{}': expected {}, got {}",
+        fname, pname, expected, obj.get_type(py).name(py));
+    let mut new_err = err::PyErr::new::<exc::TypeError, _>(py, msg);
+    #[cfg(feature="python3-sys")]
+    unsafe {
+        let cause = err.instance(py);
+        // PyException_SetCause steals the reference to `cause`.
+        ffi::PyException_SetCause(new_err.instance(py).as_ptr(), cause.steal_ptr());
+    }
+    #[cfg(feature="python27-sys")]
+    let _ = err.instance(py);
+    new_err
+}
+
+/// Maps the Rust type of a parameter (as produced by `stringify!`)
+/// to the name of the Python type it is usually extracted from.
+/// Only used for types without a Python type object; unknown types are
+/// reported by their Rust name.
+fn expected_type_name(ptype: &str) -> String {
+    let mut ptype = ptype.trim();
+    // strip references, including lifetimes and `mut`
+    while ptype.starts_with('&') {
+        ptype = ptype[1..].trim();
+        if ptype.starts_with('\'') {
+            ptype = ptype.splitn(2, char::is_whitespace).nth(1).unwrap_or("").trim();
+        }
+        if ptype.starts_with("mut ") {
+            ptype = ptype[4..].trim();
+        }
+    }
+    let ptype: String = ptype.chars().filter(|c| !c.is_whitespace()).collect();
+    let ptype = &ptype[..];
+    let (outer, inner) = match ptype.find('<') {
+        Some(pos) if ptype.ends_with('>') => (&ptype[..pos], Some(&ptype[pos+1..ptype.len()-1])),
+        _ => (ptype, None)
+    };
+    let outer = outer.rsplit("::").next().unwrap();
+    match (outer, inner) {
+        ("Option", Some(inner)) => format!("{} or None", expected_type_name(inner)),
+        ("Vec", Some(_)) => "sequence".to_owned(),
+        ("Cow", Some(inner)) => expected_type_name(inner.rsplit(',').next().unwrap()),
+        ("[u8]", None) => "bytes".to_owned(),
+        ("str", None) | ("String", None) => "str".to_owned(),
+        ("i8", None) | ("i16", None) | ("i32", None) | ("i64", None) | ("i128", None) | ("isize", None) |
+        ("u8", None) | ("u16", None) | ("u32", None) | ("u64", None) | ("u128", None) | ("usize", None) |
+        ("BigInt", None) | ("BigUint", None) => "int".to_owned(),
+        ("f32", None) | ("f64", None) => "float".to_owned(),
+        ("Complex", Some(_)) => "complex".to_owned(),
+        ("bool", None) => "bool".to_owned(),
+        ("PySequence", None) => "sequence".to_owned(),
+        ("PyIterator", None) => "iterator".to_owned(),
+        _ => ptype.to_owned()
+    }
+}
+
+// Rust 2018 support
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _cpython__argparse__stringify {
+    ($($inner:tt)*) => {
+        stringify! { $($inner)* }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::borrow::Cow;
     use python::{Python, PythonObject};
-    use objects::PyTuple;
+    use super::{ParamDescription, parse_args};
+    use objects::{exc, PyDict, PyList, PyTuple};
+    use err::PyErr;
     use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
 
     #[test]
     pub fn test_parse() {
@@ -465,6 +847,20 @@ mod test {
         assert!(called);
     }
 
+    #[test]
+    pub fn test_parse_args() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let params = [ParamDescription::new("x", false), ParamDescription::new("y", true)];
+        let tuple = ("abc",).to_py_object(py);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "y", 42).unwrap();
+        let mut output = [None, None];
+        parse_args(py, Some("f"), &params, &tuple, Some(&kwargs), &mut output).unwrap();
+        assert_eq!(output[0].as_ref().unwrap().extract::<String>(py).unwrap(), "abc");
+        assert_eq!(output[1].as_ref().unwrap().extract::<i32>(py).unwrap(), 42);
+    }
+
     #[test]
     pub fn test_default_param_type() {
         let gil_guard = Python::acquire_gil();
@@ -503,4 +899,112 @@ mod test {
         }).unwrap();
         assert!(called);
     }
+
+    #[test]
+    pub fn test_positional_and_keyword_only() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let mut called = false;
+        let tuple = (1, 2).to_py_object(py);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "c", 3).unwrap();
+        py_argparse!(py, None, &tuple, Some(&kwargs), (a: i32, /, b: i32, *, c: i32) {
+            assert_eq!((a, b, c), (1, 2, 3));
+            called = true;
+            Ok(())
+        }).unwrap();
+        assert!(called);
+
+        let tuple = (1, 2, 3).to_py_object(py);
+        let err = py_argparse!(py, Some("f"), &tuple, None, (a: i32, /, b: i32, *, c: i32) {
+            Ok((a, b, c))
+        }).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+        assert_eq!(err_message(py, err), "f() takes 2 positional arguments but 3 were given");
+
+        let tuple = PyTuple::new(py, &[]);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "a", 1).unwrap();
+        kwargs.set_item(py, "b", 2).unwrap();
+        let err = py_argparse!(py, Some("f"), &tuple, Some(&kwargs), (a: i32, /, b: i32, *, c: i32) {
+            Ok((a, b, c))
+        }).unwrap_err();
+        assert_eq!(err_message(py, err),
+            "f() got some positional-only arguments passed as keyword arguments: 'a'");
+    }
+
+    #[test]
+    pub fn test_missing_arguments() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let tuple = PyTuple::new(py, &[]);
+        let err = py_argparse!(py, Some("f"), &tuple, None, (a: i32, b: i32, *, c: i32) {
+            Ok((a, b, c))
+        }).unwrap_err();
+        assert_eq!(err_message(py, err),
+            "f() missing 2 required positional arguments: 'a' and 'b'");
+    }
+
+    #[test]
+    pub fn test_extraction_error() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let tuple = ("abc",).to_py_object(py);
+        let mut err = py_argparse!(py, Some("f"), &tuple, None, (count: i32) {
+            Ok(count)
+        }).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+        #[cfg(feature="python3-sys")]
+        {
+            let cause = err.instance(py).getattr(py, "__cause__").unwrap();
+            assert!(cause.get_type(py) == py.get_type::<exc::TypeError>());
+        }
+        assert_eq!(err_message(py, err), "f() argument 'count': expected int, got str");
+
+        let tuple = (1,).to_py_object(py);
+        let err = py_argparse!(py, Some("f"), &tuple, None, (s: &str) {
+            Ok(s.len())
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "f() argument 's': expected str, got int");
+
+        let err = py_argparse!(py, None, &tuple, None, (x: Option<Vec<i32>> = None) {
+            Ok(x)
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "function argument 'x': expected sequence or None, got int");
+
+        let err = py_argparse!(py, Some("f"), &tuple, None, (b: Cow<[u8]>) {
+            Ok(b.len())
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "f() argument 'b': expected bytes, got int");
+
+        let err = py_argparse!(py, Some("f"), &tuple, None, (b: &[u8]) {
+            Ok(b.len())
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "f() argument 'b': expected bytes, got int");
+
+        let err = py_argparse!(py, Some("f"), &tuple, None, (d: PyDict) {
+            Ok(d.len(py))
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "f() argument 'd': expected dict, got int");
+
+        let err = py_argparse!(py, Some("f"), &tuple, None, (l: Option<PyList> = None) {
+            Ok(l.is_some())
+        }).unwrap_err();
+        assert_eq!(err_message(py, err), "f() argument 'l': expected list or None, got int");
+    }
+
+    #[test]
+    pub fn test_expected_type_name() {
+        assert_eq!(super::expected_type_name("i64"), "int");
+        assert_eq!(super::expected_type_name("& 'a str"), "str");
+        assert_eq!(super::expected_type_name("Option < f64 >"), "float or None");
+        assert_eq!(super::expected_type_name("Cow<'a, str>"), "str");
+        assert_eq!(super::expected_type_name("Cow<[u8]>"), "bytes");
+        assert_eq!(super::expected_type_name("MyClass"), "MyClass");
+    }
+
+    fn err_message(py: Python, mut err: PyErr) -> String {
+        err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned()
+    }
 }
+
@@ -16,7 +16,8 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::{mem, slice, cell};
+use std::{mem, slice, cell, ops, ptr};
+use std::marker::PhantomData;
 use std::ffi::CStr;
 use ffi;
 use libc;
@@ -26,36 +27,334 @@ use python::{Python, PyDrop};
 use objects::PyObject;
 
 /// Allows access to the underlying buffer used by a python object such as `bytes`, `bytearray` or `array.array`.
-pub struct PyBuffer(Box<ffi::Py_buffer>); // use Box<> because Python expects that the Py_buffer struct has a stable memory address
+pub struct PyBuffer(Box<ffi::Py_buffer>, Vec<ffi::Py_ssize_t>); // use Box<> because Python expects that the Py_buffer struct has a stable memory address
+// The Vec holds the shape and strides that we fill in if the exporter doesn't provide them.
 
 // PyBuffer is thread-safe: the shape of the buffer is immutable while a Py_buffer exists.
 // Accessing the buffer contents is protected using the GIL.
 unsafe impl Send for PyBuffer {}
 unsafe impl Sync for PyBuffer {}
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ElementType {
     SignedInteger { bytes: usize },
     UnsignedInteger { bytes: usize },
     Bool,
     Float { bytes: usize },
+    /// Complex number consisting of two floats; `bytes` is the size of the whole number.
+    Complex { bytes: usize },
     Unknown
 }
 
 impl ElementType {
+    /// Gets the element type of a format string describing a single scalar.
+    ///
+    /// The byte order is ignored; use `ItemFormat::parse()` to get it.
+    /// Returns `Unknown` for formats that do not describe a single scalar.
     pub fn from_format(format: &CStr) -> ElementType {
-        let slice = format.to_bytes();
-        if slice.len() == 1 {
-            native_element_type_from_type_char(slice[0])
-        } else if slice.len() == 2 {
-            match slice[0] {
-                b'@' => native_element_type_from_type_char(slice[1]),
-                b'=' | b'<' | b'>' | b'!' => standard_element_type_from_type_char(slice[1]),
-                _ => ElementType::Unknown
+        match ItemFormat::parse(format) {
+            Some(ItemFormat::Scalar { element, .. }) => element,
+            _ => ElementType::Unknown
+        }
+    }
+
+    fn size(&self) -> usize {
+        match *self {
+            ElementType::SignedInteger { bytes }
+            | ElementType::UnsignedInteger { bytes }
+            | ElementType::Float { bytes }
+            | ElementType::Complex { bytes } => bytes,
+            ElementType::Bool => 1,
+            ElementType::Unknown => 0
+        }
+    }
+
+    fn alignment(&self) -> usize {
+        match *self {
+            ElementType::Complex { bytes } => bytes / 2,
+            _ => self.size()
+        }
+    }
+}
+
+/// Byte order of a buffer item, as given by the prefix character in the format string.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ByteOrder {
+    /// The byte order of the platform (`@`, `=` or no prefix).
+    Native,
+    /// Little endian (`<`).
+    LittleEndian,
+    /// Big endian (`>` or `!`).
+    BigEndian
+}
+
+impl ByteOrder {
+    /// Gets whether values in this byte order can be used on this platform without swapping bytes.
+    pub fn is_native(self) -> bool {
+        self.is_little_endian() == cfg!(target_endian = "little")
+    }
+
+    fn is_little_endian(self) -> bool {
+        match self {
+            ByteOrder::Native => cfg!(target_endian = "little"),
+            ByteOrder::LittleEndian => true,
+            ByteOrder::BigEndian => false
+        }
+    }
+}
+
+/// Structured description of a buffer item, parsed from a format string
+/// in the syntax of the `struct` module with the extensions from PEP 3118.
+///
+/// Sizes and field offsets follow the rules of the `struct` module:
+/// items are aligned only in native mode (`@` or no prefix), and no padding is added
+/// at the end of structures.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemFormat {
+    /// A number or boolean, e.g. `i`, `<d`, `e`, `?` or `Zd`.
+    Scalar { element: ElementType, byte_order: ByteOrder },
+    /// A fixed-length byte string (`s` or `p`, with the length as repeat count).
+    Bytes { len: usize },
+    /// Padding bytes (`x`).
+    Padding { len: usize },
+    /// A pointer to a Python object (`O`).
+    Object,
+    /// A pointer to an item (`&`).
+    Pointer(Box<ItemFormat>),
+    /// A fixed-size array of items, from a repeat count (`3i`) or a shape (`(2,3)i`).
+    Array { shape: Vec<usize>, item: Box<ItemFormat> },
+    /// A structure (`T{...}`), or a format string consisting of several items.
+    /// Padding items are not included in `fields`.
+    Struct { fields: Vec<FormatField>, size: usize }
+}
+
+/// Field of an `ItemFormat::Struct`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatField {
+    /// Name of the field, given as `:name:` after the item.
+    pub name: Option<String>,
+    /// Offset of the field from the start of the structure, in bytes.
+    pub offset: usize,
+    /// Format of the field.
+    pub format: ItemFormat
+}
+
+impl ItemFormat {
+    /// Parses a format string.
+    ///
+    /// Returns `None` if the format string is invalid, or uses
+    /// unsupported item types (e.g. `g` or `u`).
+    pub fn parse(format: &CStr) -> Option<ItemFormat> {
+        let mut parser = FormatParser {
+            input: format.to_bytes(),
+            pos: 0,
+            byte_order: ByteOrder::Native,
+            native: true
+        };
+        let mut items = parser.parse_items(None)?;
+        if items.len() == 1 && items[0].name.is_none() {
+            items.pop().map(|item| item.format)
+        } else if items.is_empty() {
+            None
+        } else {
+            make_struct(items)
+        }
+    }
+
+    /// Gets the size of the item, in bytes.
+    ///
+    /// `parse()` rejects formats whose size does not fit into `usize`.
+    pub fn size(&self) -> usize {
+        match *self {
+            ItemFormat::Scalar { element, .. } => element.size(),
+            ItemFormat::Bytes { len } | ItemFormat::Padding { len } => len,
+            ItemFormat::Object | ItemFormat::Pointer(_) => mem::size_of::<*const u8>(),
+            ItemFormat::Array { ref shape, ref item } => shape.iter().product::<usize>() * item.size(),
+            ItemFormat::Struct { size, .. } => size
+        }
+    }
+
+    fn alignment(&self) -> usize {
+        match *self {
+            ItemFormat::Scalar { element, .. } => element.alignment(),
+            ItemFormat::Bytes { .. } | ItemFormat::Padding { .. } => 1,
+            ItemFormat::Object | ItemFormat::Pointer(_) => mem::align_of::<*const u8>(),
+            ItemFormat::Array { ref item, .. } => item.alignment(),
+            ItemFormat::Struct { ref fields, .. } => fields.iter().map(|f| f.format.alignment()).max().unwrap_or(1)
+        }
+    }
+}
+
+struct ParsedItem {
+    name: Option<String>,
+    format: ItemFormat,
+    // alignment in effect for the item; 1 if it was not in native mode
+    alignment: usize
+}
+
+struct FormatParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    byte_order: ByteOrder,
+    // whether native sizes and alignment are used ('@')
+    native: bool
+}
+
+impl <'a> FormatParser<'a> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.pos < self.input.len() && (self.input[self.pos] as char).is_whitespace() {
+            self.pos += 1;
+        }
+        self.input.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        ::std::str::from_utf8(&self.input[start..self.pos]).ok()?.parse().ok()
+    }
+
+    // Parses items until `end`, or until the end of the input if `end` is None.
+    fn parse_items(&mut self, end: Option<u8>) -> Option<Vec<ParsedItem>> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek() {
+                None if end.is_none() => return Some(items),
+                None => return None,
+                Some(c) if Some(c) == end => {
+                    self.pos += 1;
+                    return Some(items);
+                }
+                Some(_) => items.push(self.parse_item()?)
             }
+        }
+    }
+
+    fn parse_item(&mut self) -> Option<ParsedItem> {
+        loop {
+            let (byte_order, native) = match self.peek() {
+                Some(b'@') => (ByteOrder::Native, true),
+                Some(b'=') => (ByteOrder::Native, false),
+                Some(b'<') => (ByteOrder::LittleEndian, false),
+                Some(b'>') | Some(b'!') => (ByteOrder::BigEndian, false),
+                _ => break
+            };
+            self.byte_order = byte_order;
+            self.native = native;
+            self.pos += 1;
+        }
+        let mut shape = Vec::new();
+        if self.peek() == Some(b'(') {
+            self.pos += 1;
+            loop {
+                self.peek();
+                shape.push(self.parse_number()?);
+                match self.next()? {
+                    b',' => {},
+                    b')' => break,
+                    _ => return None
+                }
+            }
+        }
+        self.peek();
+        let count = self.parse_number();
+        let format = match self.next()? {
+            b'x' => ItemFormat::Padding { len: count.unwrap_or(1) },
+            b's' | b'p' => ItemFormat::Bytes { len: count.unwrap_or(1) },
+            c => {
+                let item = match c {
+                    b'O' => ItemFormat::Object,
+                    b'&' => ItemFormat::Pointer(Box::new(self.parse_item()?.format)),
+                    b'T' => {
+                        if self.next()? != b'{' {
+                            return None;
+                        }
+                        let (byte_order, native) = (self.byte_order, self.native);
+                        let items = self.parse_items(Some(b'}'))?;
+                        self.byte_order = byte_order;
+                        self.native = native;
+                        make_struct(items)?
+                    }
+                    b'Z' => {
+                        let c = self.next()?;
+                        self.scalar(complex_element_type(self.element_type(c)))?
+                    }
+                    c => {
+                        let element = self.element_type(c);
+                        self.scalar(element)?
+                    }
+                };
+                if let Some(count) = count {
+                    shape.push(count);
+                }
+                if shape.is_empty() || shape == [1] {
+                    item
+                } else {
+                    // make sure that `size()` cannot overflow
+                    shape.iter().fold(Some(item.size()), |size, &n| size?.checked_mul(n))?;
+                    ItemFormat::Array { shape: shape, item: Box::new(item) }
+                }
+            }
+        };
+        let name = if self.peek() == Some(b':') {
+            self.pos += 1;
+            let start = self.pos;
+            let len = self.input[start..].iter().position(|&c| c == b':')?;
+            self.pos += len + 1;
+            Some(String::from_utf8_lossy(&self.input[start..start + len]).into_owned())
         } else {
-            ElementType::Unknown
+            None
+        };
+        let alignment = if self.native { format.alignment() } else { 1 };
+        Some(ParsedItem { name: name, format: format, alignment: alignment })
+    }
+
+    fn element_type(&self, type_char: u8) -> ElementType {
+        if self.native {
+            native_element_type_from_type_char(type_char)
+        } else {
+            standard_element_type_from_type_char(type_char)
+        }
+    }
+
+    fn scalar(&self, element: ElementType) -> Option<ItemFormat> {
+        if element == ElementType::Unknown {
+            None
+        } else {
+            Some(ItemFormat::Scalar { element: element, byte_order: self.byte_order })
+        }
+    }
+}
+
+// Lays out the items as a structure.
+// Returns `None` if the size of the structure overflows `usize`.
+fn make_struct(items: Vec<ParsedItem>) -> Option<ItemFormat> {
+    let mut fields = Vec::new();
+    let mut offset: usize = 0;
+    for item in items {
+        offset = offset.checked_add(item.alignment - 1)? / item.alignment * item.alignment;
+        let size = item.format.size();
+        match item.format {
+            ItemFormat::Padding { .. } => {},
+            format => fields.push(FormatField { name: item.name, offset: offset, format: format })
         }
+        offset = offset.checked_add(size)?;
+    }
+    Some(ItemFormat::Struct { fields: fields, size: offset })
+}
+
+fn complex_element_type(component: ElementType) -> ElementType {
+    match component {
+        ElementType::Float { bytes } => ElementType::Complex { bytes: 2 * bytes },
+        _ => ElementType::Unknown
     }
 }
 
@@ -105,22 +404,6 @@ fn standard_element_type_from_type_char(type_char: u8) -> ElementType {
     }
 }
 
-#[cfg(target_endian = "little")]
-fn is_matching_endian(c: u8) -> bool {
-    match c {
-        b'@' | b'=' | b'<' => true,
-        _ => false
-    }
-}
-
-#[cfg(target_endian = "big")]
-fn is_matching_endian(c: u8) -> bool {
-    match c {
-        b'@' | b'=' | b'>' | b'!' => true,
-        _ => false
-    }
-}
-
 /// Trait implemented for possible element types of `PyBuffer`.
 pub unsafe trait Element {
     /// Gets whether the element specified in the format string is potentially compatible.
@@ -128,20 +411,96 @@ pub unsafe trait Element {
     fn is_compatible_format(format: &CStr) -> bool;
 }
 
-fn validate(b: &ffi::Py_buffer) {
-    // shape and stride information must be provided when we use PyBUF_FULL_RO
-    assert!(!b.shape.is_null());
-    assert!(!b.strides.is_null());
+/// Element types that have a native format string, and thus can be exposed
+/// to Python without a format string supplied by the user (see `PyMemoryView::from_owner()`).
+pub unsafe trait NativeElement: Element + Copy {
+    /// Gets the struct-module format string describing the element type in native byte order.
+    fn format() -> &'static CStr;
+}
+
+/// Flags for `PyBuffer::get_with_flags()`, specifying which kind of buffer the consumer can handle.
+///
+/// Corresponds to the `PyBUF_*` constants of the buffer protocol; combine flags with `|`.
+/// If the exporter cannot provide the requested kind of buffer, it raises `BufferError`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BufferFlags(libc::c_int);
+
+impl BufferFlags {
+    /// `PyBUF_SIMPLE`: request a plain contiguous block of bytes.
+    pub const SIMPLE: BufferFlags = BufferFlags(ffi::PyBUF_SIMPLE);
+    /// `PyBUF_WRITABLE`: request a writable buffer.
+    pub const WRITABLE: BufferFlags = BufferFlags(ffi::PyBUF_WRITABLE);
+    /// `PyBUF_FORMAT`: request the format string.
+    pub const FORMAT: BufferFlags = BufferFlags(ffi::PyBUF_FORMAT);
+    /// `PyBUF_ND`: request the shape; the buffer must be C-contiguous.
+    pub const ND: BufferFlags = BufferFlags(ffi::PyBUF_ND);
+    /// `PyBUF_STRIDES`: request shape and strides; the buffer must not require suboffsets.
+    pub const STRIDES: BufferFlags = BufferFlags(ffi::PyBUF_STRIDES);
+    /// `PyBUF_C_CONTIGUOUS`: request a C-contiguous buffer, with shape and strides.
+    pub const C_CONTIGUOUS: BufferFlags = BufferFlags(ffi::PyBUF_C_CONTIGUOUS);
+    /// `PyBUF_F_CONTIGUOUS`: request a Fortran-contiguous buffer, with shape and strides.
+    pub const F_CONTIGUOUS: BufferFlags = BufferFlags(ffi::PyBUF_F_CONTIGUOUS);
+    /// `PyBUF_ANY_CONTIGUOUS`: request a C- or Fortran-contiguous buffer, with shape and strides.
+    pub const ANY_CONTIGUOUS: BufferFlags = BufferFlags(ffi::PyBUF_ANY_CONTIGUOUS);
+    /// `PyBUF_INDIRECT`: request shape, strides and suboffsets; accepts any buffer layout.
+    pub const INDIRECT: BufferFlags = BufferFlags(ffi::PyBUF_INDIRECT);
+    /// `PyBUF_FULL_RO`: any buffer layout, with format. Used by `PyBuffer::get()`.
+    pub const FULL_RO: BufferFlags = BufferFlags(ffi::PyBUF_FULL_RO);
+    /// `PyBUF_FULL`: any writable buffer layout, with format.
+    pub const FULL: BufferFlags = BufferFlags(ffi::PyBUF_FULL);
+
+    /// Gets the raw `PyBUF_*` flags.
+    #[inline]
+    pub fn bits(self) -> libc::c_int {
+        self.0
+    }
+
+    /// Gets whether all flags in `other` are also set in `self`.
+    #[inline]
+    pub fn contains(self, other: BufferFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for BufferFlags {
+    type Output = BufferFlags;
+
+    #[inline]
+    fn bitor(self, other: BufferFlags) -> BufferFlags {
+        BufferFlags(self.0 | other.0)
+    }
 }
 
 impl PyBuffer {
     /// Get the underlying buffer from the specified python object.
+    ///
+    /// Accepts any buffer layout; equivalent to `get_with_flags(py, obj, BufferFlags::FULL_RO)`.
     pub fn get(py: Python, obj: &PyObject) -> PyResult<PyBuffer> {
+        PyBuffer::get_with_flags(py, obj, BufferFlags::FULL_RO)
+    }
+
+    /// Get the underlying buffer from the specified python object, requesting a specific kind of buffer.
+    ///
+    /// Fails with `BufferError` (raised by the exporter) if the object cannot provide such a buffer.
+    /// Depending on `flags`, the returned buffer guarantees:
+    ///  * `WRITABLE`: `readonly()` is false.
+    ///  * `FORMAT`: `format()` is the format provided by the exporter.
+    ///    Without this flag, `format()` returns `"B"`, but `item_size()` may still be larger than 1.
+    ///  * `C_CONTIGUOUS`, `F_CONTIGUOUS` or `ANY_CONTIGUOUS`: `is_c_contiguous()`,
+    ///    `is_fortran_contiguous()`, or one of both is true.
+    ///  * `ND` without `STRIDES`: the buffer is C-contiguous.
+    ///  * `SIMPLE`: the buffer is a contiguous block of `len_bytes()` bytes with `dimensions() == 1`.
+    ///  * without `INDIRECT`: `suboffsets()` is `None`.
+    ///
+    /// `shape()` and `strides()` are always available; if the exporter doesn't provide them,
+    /// they are derived from the buffer length, which is valid for the contiguous buffers
+    /// that exporters return in that case.
+    pub fn get_with_flags(py: Python, obj: &PyObject, flags: BufferFlags) -> PyResult<PyBuffer> {
         unsafe {
             let mut buf = Box::new(mem::zeroed::<ffi::Py_buffer>());
-            err::error_on_minusone(py, ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *buf, ffi::PyBUF_FULL_RO))?;
-            validate(&buf);
-            Ok(PyBuffer(buf))
+            err::error_on_minusone(py, ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *buf, flags.bits()))?;
+            let storage = fill_shape_and_strides(&mut buf);
+            Ok(PyBuffer(buf, storage))
         }
     }
 
@@ -214,6 +573,9 @@ impl PyBuffer {
     /// However, dimensions of length 0 are possible and might need special attention.
     #[inline]
     pub fn shape(&self) -> &[usize] {
+        if self.0.ndim == 0 {
+            return &[];
+        }
         unsafe {
             slice::from_raw_parts(self.0.shape as *const usize, self.0.ndim as usize)
         }
@@ -225,6 +587,9 @@ impl PyBuffer {
     /// but a consumer MUST be able to handle the case `strides[n] <= 0`.
     #[inline]
     pub fn strides(&self) -> &[isize] {
+        if self.0.ndim == 0 {
+            return &[];
+        }
         unsafe {
             slice::from_raw_parts(self.0.strides, self.0.ndim as usize)
         }
@@ -256,6 +621,13 @@ impl PyBuffer {
         }
     }
 
+    /// Parses the format string into a structured description of a single item.
+    ///
+    /// Returns `None` if the format string is invalid or unsupported; see `ItemFormat::parse()`.
+    pub fn item_format(&self) -> Option<ItemFormat> {
+        ItemFormat::parse(self.format())
+    }
+
     /// Gets whether the buffer is contiguous in C-style order (last index varies fastest when visiting items in order of memory address).
     #[inline]
     pub fn is_c_contiguous(&self) -> bool {
@@ -362,6 +734,96 @@ impl PyBuffer {
         }
     }
 
+    /// Calls `f` with the buffer memory as a slice.
+    ///
+    /// Fails with `BufferError` if:
+    ///  * the buffer format is not compatible with `T`
+    ///  * alignment and size of buffer elements don't match the expectations for type `T`
+    ///  * the buffer is not C-style contiguous
+    ///
+    /// Unlike `as_slice()`, the slice can be passed to code that expects `&[T]`.
+    /// `f` may release the GIL using `py.allow_threads()`, e.g. to process the slice in parallel.
+    ///
+    /// # Safety
+    /// The buffer memory must not be modified while `f` runs.
+    /// In particular, `f` must not run Python code that could write to the buffer, and
+    /// if `f` releases the GIL, no other thread may write to the buffer until `f` returns.
+    pub unsafe fn with_slice<T, R, F>(&self, py: Python, f: F) -> PyResult<R>
+        where T: Element, F: FnOnce(&[T]) -> R
+    {
+        self.check_slice::<T>(py)?;
+        if self.item_count() == 0 {
+            // the buffer pointer may be null for empty buffers
+            return Ok(f(&[]));
+        }
+        Ok(f(slice::from_raw_parts(self.0.buf as *const T, self.item_count())))
+    }
+
+    /// Calls `f` with the buffer memory as a mutable slice.
+    ///
+    /// Fails with `BufferError` if:
+    ///  * the buffer is read-only
+    ///  * the buffer format is not compatible with `T`
+    ///  * alignment and size of buffer elements don't match the expectations for type `T`
+    ///  * the buffer is not C-style contiguous
+    ///
+    /// Unlike `as_mut_slice()`, the slice can be passed to code that expects `&mut [T]`.
+    /// `f` may release the GIL using `py.allow_threads()`, e.g. to process the slice in parallel.
+    ///
+    /// # Safety
+    /// `f` must have exclusive access to the buffer memory while it runs.
+    /// In particular, `f` must not run Python code that could access the buffer, and
+    /// if `f` releases the GIL, no other thread may access the buffer until `f` returns.
+    /// Note that other objects may share the memory of this buffer (e.g. other memoryviews
+    /// or numpy views of the same array).
+    pub unsafe fn with_mut_slice<T, R, F>(&self, py: Python, f: F) -> PyResult<R>
+        where T: Element, F: FnOnce(&mut [T]) -> R
+    {
+        if self.readonly() {
+            buffer_readonly_error(py)?;
+        }
+        self.check_slice::<T>(py)?;
+        if self.item_count() == 0 {
+            return Ok(f(&mut []));
+        }
+        Ok(f(slice::from_raw_parts_mut(self.0.buf as *mut T, self.item_count())))
+    }
+
+    fn check_slice<T: Element>(&self, py: Python) -> PyResult<()> {
+        if !T::is_compatible_format(self.format()) || mem::size_of::<T>() != self.item_size()
+            || (self.0.buf as usize) % mem::align_of::<T>() != 0
+        {
+            return incompatible_format_error(py);
+        }
+        if !self.is_c_contiguous() {
+            return Err(err::PyErr::new::<exc::BufferError, _>(py, "Buffer is not C-contiguous."));
+        }
+        Ok(())
+    }
+
+    /// Gets an N-dimensional view of the buffer memory.
+    ///
+    /// Unlike `as_slice()`, this works for any memory layout, including
+    /// non-contiguous buffers (e.g. numpy's `a[:, ::2]`), negative strides and
+    /// PIL-style arrays with suboffsets.
+    ///
+    /// This function succeeds if:
+    ///  * the buffer format is compatible with `T`
+    ///  * the size of buffer elements is matching the size of type `T`
+    pub fn as_array_view<'a, T: Element+Copy>(&'a self, _py: Python<'a>) -> Option<ArrayView<'a, T>> {
+        if mem::size_of::<T>() == self.item_size() && T::is_compatible_format(self.format()) {
+            Some(ArrayView {
+                ptr: self.0.buf as *const u8,
+                shape: self.shape(),
+                strides: self.strides(),
+                suboffsets: self.suboffsets(),
+                marker: PhantomData
+            })
+        } else {
+            None
+        }
+    }
+
     /// Copies the buffer elements to the specified slice.
     /// If the buffer is multi-dimensional, the elements are written in C-style order.
     ///
@@ -492,6 +954,167 @@ impl PyBuffer {
     }
 }
 
+/// N-dimensional view of the memory of a `PyBuffer`, with elements of type `T`.
+///
+/// Created by `PyBuffer::as_array_view()`. Elements are located using the buffer's
+/// shape, strides and suboffsets, so the view works for any memory layout.
+/// Elements are read by value, because they may be unaligned and
+/// any call into the Python runtime may modify them.
+pub struct ArrayView<'a, T: 'a> {
+    ptr: *const u8,
+    shape: &'a [usize],
+    strides: &'a [isize],
+    suboffsets: Option<&'a [isize]>,
+    marker: PhantomData<&'a ReadOnlyCell<T>>
+}
+
+impl <'a, T: Element+Copy> ArrayView<'a, T> {
+    /// Gets the number of dimensions of the view.
+    ///
+    /// Is 0 if the view refers to a single element.
+    #[inline]
+    pub fn dimensions(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// Gets the length of the view in each dimension.
+    #[inline]
+    pub fn shape(&self) -> &'a [usize] {
+        self.shape
+    }
+
+    /// Gets the number of bytes to skip to get to the next element in each dimension.
+    #[inline]
+    pub fn strides(&self) -> &'a [isize] {
+        self.strides
+    }
+
+    /// Gets the suboffsets of the view; see `PyBuffer::suboffsets()`.
+    #[inline]
+    pub fn suboffsets(&self) -> Option<&'a [isize]> {
+        self.suboffsets
+    }
+
+    /// Gets the total number of elements in the view.
+    #[inline]
+    pub fn item_count(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    // Moves `ptr` to index `index` in dimension `dim`, dereferencing it if the dimension has a suboffset.
+    unsafe fn step(&self, ptr: *const u8, dim: usize, index: usize) -> *const u8 {
+        let ptr = ptr.offset(self.strides[dim] * index as isize);
+        match self.suboffsets {
+            Some(suboffsets) if suboffsets[dim] >= 0 => (*(ptr as *const *const u8)).offset(suboffsets[dim]),
+            _ => ptr
+        }
+    }
+
+    /// Gets the element at the specified indices.
+    ///
+    /// Returns `None` if `indices.len() != self.dimensions()`, or if any index is out of bounds.
+    pub fn get(&self, indices: &[usize]) -> Option<T> {
+        if indices.len() != self.shape.len() {
+            return None;
+        }
+        let mut ptr = self.ptr;
+        for (dim, &index) in indices.iter().enumerate() {
+            if index >= self.shape[dim] {
+                return None;
+            }
+            ptr = unsafe { self.step(ptr, dim, index) };
+        }
+        Some(unsafe { ptr::read_unaligned(ptr as *const T) })
+    }
+
+    /// Gets the sub-view at index `index` in the first dimension.
+    ///
+    /// For a 2-dimensional view, this is the row at `index`.
+    /// Returns `None` if the view has 0 dimensions, or if `index` is out of bounds.
+    pub fn row(&self, index: usize) -> Option<ArrayView<'a, T>> {
+        if self.shape.is_empty() || index >= self.shape[0] {
+            return None;
+        }
+        Some(ArrayView {
+            ptr: unsafe { self.step(self.ptr, 0, index) },
+            shape: &self.shape[1..],
+            strides: &self.strides[1..],
+            suboffsets: self.suboffsets.map(|s| &s[1..]),
+            marker: PhantomData
+        })
+    }
+
+    /// Returns an iterator over the sub-views along the first dimension.
+    ///
+    /// The iterator is empty if the view has 0 dimensions.
+    pub fn rows(&self) -> Rows<'a, T> {
+        Rows {
+            view: *self,
+            index: 0
+        }
+    }
+
+    /// Copies the elements of the view to the specified slice, in C-style order.
+    ///
+    /// Panics if `target.len() != self.item_count()`.
+    pub fn copy_to_slice(&self, target: &mut [T]) {
+        assert_eq!(target.len(), self.item_count(), "slice length does not match view length");
+        let mut targets = target.iter_mut();
+        self.for_each_impl(self.ptr, 0, &mut |value| *targets.next().unwrap() = value);
+    }
+
+    /// Copies the elements of the view to a newly allocated vector, in C-style order.
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.item_count());
+        self.for_each_impl(self.ptr, 0, &mut |value| vec.push(value));
+        vec
+    }
+
+    fn for_each_impl(&self, ptr: *const u8, dim: usize, f: &mut FnMut(T)) {
+        if dim == self.shape.len() {
+            f(unsafe { ptr::read_unaligned(ptr as *const T) });
+        } else {
+            for index in 0..self.shape[dim] {
+                let ptr = unsafe { self.step(ptr, dim, index) };
+                self.for_each_impl(ptr, dim + 1, f);
+            }
+        }
+    }
+}
+
+impl <'a, T> Clone for ArrayView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl <'a, T> Copy for ArrayView<'a, T> {}
+
+/// Iterator over the rows of an `ArrayView`; created by `ArrayView::rows()`.
+pub struct Rows<'a, T: 'a> {
+    view: ArrayView<'a, T>,
+    index: usize
+}
+
+impl <'a, T: Element+Copy> Iterator for Rows<'a, T> {
+    type Item = ArrayView<'a, T>;
+
+    fn next(&mut self) -> Option<ArrayView<'a, T>> {
+        let row = self.view.row(self.index);
+        if row.is_some() {
+            self.index += 1;
+        }
+        row
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.view.shape.first().map_or(0, |&n| n - self.index);
+        (len, Some(len))
+    }
+}
+
+impl <'a, T: Element+Copy> ExactSizeIterator for Rows<'a, T> {}
+
 fn slice_length_error(py: Python) -> PyResult<()> {
     Err(err::PyErr::new::<exc::BufferError, _>(py, "Slice length does not match buffer length."))
 }
@@ -504,17 +1127,57 @@ fn buffer_readonly_error(py: Python) -> PyResult<()> {
     Err(err::PyErr::new::<exc::BufferError, _>(py, "Cannot write to read-only buffer."))
 }
 
+// Fills in shape and strides of a C-contiguous buffer, if they weren't provided by the exporter.
+// Returns the storage that the filled in pointers point to.
+unsafe fn fill_shape_and_strides(buf: &mut ffi::Py_buffer) -> Vec<ffi::Py_ssize_t> {
+    let ndim = buf.ndim as usize;
+    let mut storage = Vec::new();
+    if ndim == 0 || !buf.strides.is_null() {
+        return storage;
+    }
+    // reserve all space up front: the pointers into the vector must remain valid
+    storage.reserve_exact(2 * ndim);
+    if buf.shape.is_null() {
+        // only happens without PyBUF_ND, in which case the buffer is one-dimensional
+        storage.push(if buf.itemsize == 0 { 0 } else { buf.len / buf.itemsize });
+        buf.shape = storage.as_mut_ptr();
+    }
+    let mut stride = buf.itemsize;
+    let first_stride = storage.len();
+    storage.resize(first_stride + ndim, 0);
+    for dim in (0..ndim).rev() {
+        storage[first_stride + dim] = stride;
+        stride *= *buf.shape.offset(dim as isize);
+    }
+    buf.strides = storage.as_mut_ptr().offset(first_stride as isize);
+    storage
+}
+
+impl PyBuffer {
+    fn release(&mut self) {
+        if !self.1.is_empty() {
+            // don't pass our own shape and strides back to the exporter
+            if self.0.shape == self.1.as_mut_ptr() {
+                self.0.shape = ptr::null_mut();
+            }
+            self.0.strides = ptr::null_mut();
+            self.1 = Vec::new();
+        }
+        unsafe { ffi::PyBuffer_Release(&mut *self.0) }
+    }
+}
+
 impl PyDrop for PyBuffer {
     #[inline]
     fn release_ref(mut self, _py: Python) {
-        unsafe { ffi::PyBuffer_Release(&mut *self.0) }
+        self.release()
     }
 }
 
 impl Drop for PyBuffer {
     fn drop(&mut self) {
         let _gil_guard = Python::acquire_gil();
-        unsafe { ffi::PyBuffer_Release(&mut *self.0) }
+        self.release()
     }
 }
 
@@ -537,41 +1200,287 @@ impl <T: Copy> ReadOnlyCell<T> {
     }
 }
 
+// Gets whether `format` describes a single scalar of the specified type and byte order.
+fn is_matching_scalar(format: &CStr, element: ElementType, byte_order: ByteOrder) -> bool {
+    match ItemFormat::parse(format) {
+        Some(ItemFormat::Scalar { element: e, byte_order: b }) => {
+            // `bool` is not a valid element type: a `?` buffer may contain bytes other than 0 and 1.
+            // Such buffers are accessed as `u8` instead.
+            let e = match e {
+                ElementType::Bool => ElementType::UnsignedInteger { bytes: 1 },
+                e => e
+            };
+            e == element && (element.size() == 1 || b.is_little_endian() == byte_order.is_little_endian())
+        }
+        _ => false
+    }
+}
+
 macro_rules! impl_element(
-    ($t:ty, $f:ident) => {
+    ($t:ty, $f:ident, $format:expr) => {
         unsafe impl Element for $t {
             fn is_compatible_format(format: &CStr) -> bool {
-                let slice = format.to_bytes();
-                if slice.len() > 1 && !is_matching_endian(slice[0]) {
-                    return false;
-                }
-                ElementType::from_format(format) == ElementType::$f { bytes: mem::size_of::<$t>() }
+                is_matching_scalar(format, ElementType::$f { bytes: mem::size_of::<$t>() }, ByteOrder::Native)
+            }
+        }
+
+        unsafe impl NativeElement for $t {
+            fn format() -> &'static CStr {
+                cstr!($format)
+            }
+        }
+
+        unsafe impl Element for LittleEndian<$t> {
+            fn is_compatible_format(format: &CStr) -> bool {
+                is_matching_scalar(format, ElementType::$f { bytes: mem::size_of::<$t>() }, ByteOrder::LittleEndian)
+            }
+        }
+
+        unsafe impl Element for BigEndian<$t> {
+            fn is_compatible_format(format: &CStr) -> bool {
+                is_matching_scalar(format, ElementType::$f { bytes: mem::size_of::<$t>() }, ByteOrder::BigEndian)
             }
         }
     }
 );
 
-impl_element!(u8, UnsignedInteger);
-impl_element!(u16, UnsignedInteger);
-impl_element!(u32, UnsignedInteger);
-impl_element!(u64, UnsignedInteger);
-impl_element!(usize, UnsignedInteger);
-impl_element!(i8, SignedInteger);
-impl_element!(i16, SignedInteger);
-impl_element!(i32, SignedInteger);
-impl_element!(i64, SignedInteger);
-impl_element!(isize, SignedInteger);
-impl_element!(f32, Float);
-impl_element!(f64, Float);
+impl_element!(u8, UnsignedInteger, "B");
+impl_element!(u16, UnsignedInteger, "H");
+impl_element!(u32, UnsignedInteger, "I");
+impl_element!(u64, UnsignedInteger, "Q");
+impl_element!(usize, UnsignedInteger, "N");
+impl_element!(i8, SignedInteger, "b");
+impl_element!(i16, SignedInteger, "h");
+impl_element!(i32, SignedInteger, "i");
+impl_element!(i64, SignedInteger, "q");
+impl_element!(isize, SignedInteger, "n");
+impl_element!(f32, Float, "f");
+impl_element!(f64, Float, "d");
+#[cfg(feature="num-complex")]
+impl_element!(::num_complex::Complex<f32>, Complex, "Zf");
+#[cfg(feature="num-complex")]
+impl_element!(::num_complex::Complex<f64>, Complex, "Zd");
+
+/// Buffer element stored in little-endian byte order, e.g. for the format `<i`.
+///
+/// Use `LittleEndian<T>` instead of `T` to access such buffers on any platform;
+/// `get()` returns the value in native byte order.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct LittleEndian<T>(T);
+
+/// Buffer element stored in big-endian byte order, e.g. for the format `>i` or `!i`.
+///
+/// Use `BigEndian<T>` instead of `T` to access such buffers on any platform;
+/// `get()` returns the value in native byte order.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct BigEndian<T>(T);
+
+impl <T: SwapBytes> LittleEndian<T> {
+    /// Creates an element from a value in native byte order.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        LittleEndian(if cfg!(target_endian = "little") { value } else { value.swap_bytes() })
+    }
+
+    /// Gets the value in native byte order.
+    #[inline]
+    pub fn get(self) -> T {
+        if cfg!(target_endian = "little") { self.0 } else { self.0.swap_bytes() }
+    }
+}
+
+impl <T: SwapBytes> BigEndian<T> {
+    /// Creates an element from a value in native byte order.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        BigEndian(if cfg!(target_endian = "big") { value } else { value.swap_bytes() })
+    }
+
+    /// Gets the value in native byte order.
+    #[inline]
+    pub fn get(self) -> T {
+        if cfg!(target_endian = "big") { self.0 } else { self.0.swap_bytes() }
+    }
+}
+
+/// Types that can be used with `LittleEndian` and `BigEndian`.
+pub trait SwapBytes: Copy {
+    /// Reverses the byte order of the value.
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! impl_swap_bytes(
+    ($t:ty) => {
+        impl SwapBytes for $t {
+            #[inline]
+            fn swap_bytes(self) -> $t {
+                <$t>::swap_bytes(self)
+            }
+        }
+    };
+    ($t:ty, $bits:ty) => {
+        impl SwapBytes for $t {
+            #[inline]
+            fn swap_bytes(self) -> $t {
+                <$t>::from_bits(self.to_bits().swap_bytes())
+            }
+        }
+    }
+);
+
+impl_swap_bytes!(u8);
+impl_swap_bytes!(u16);
+impl_swap_bytes!(u32);
+impl_swap_bytes!(u64);
+impl_swap_bytes!(usize);
+impl_swap_bytes!(i8);
+impl_swap_bytes!(i16);
+impl_swap_bytes!(i32);
+impl_swap_bytes!(i64);
+impl_swap_bytes!(isize);
+impl_swap_bytes!(f32, u32);
+impl_swap_bytes!(f64, u64);
 
 #[cfg(test)]
 mod test {
     use std;
     use python::{Python, PythonObject, PyDrop};
     use conversion::ToPyObject;
-    use objects::{PySequence, PyList, PyTuple, PyIterator};
+    use objects::{PySequence, PyList, PyTuple, PyIterator, exc};
     use objectprotocol::ObjectProtocol;
-    use super::PyBuffer;
+    use std::ffi::CStr;
+    use super::{PyBuffer, BufferFlags, ElementType, ItemFormat, FormatField, ByteOrder, BigEndian, LittleEndian};
+
+    #[test]
+    fn test_complex_element_type() {
+        let format = |s: &'static [u8]| CStr::from_bytes_with_nul(s).unwrap();
+        assert!(ElementType::from_format(format(b"Zd\0")) == ElementType::Complex { bytes: 16 });
+        assert!(ElementType::from_format(format(b"<Zf\0")) == ElementType::Complex { bytes: 8 });
+        assert!(ElementType::from_format(format(b"Zi\0")) == ElementType::Unknown);
+    }
+
+    #[test]
+    fn test_item_format() {
+        use super::ItemFormat::*;
+        use super::ElementType::*;
+        let parse = |s: &'static [u8]| ItemFormat::parse(CStr::from_bytes_with_nul(s).unwrap());
+        assert_eq!(parse(b"<i\0"), Some(Scalar { element: SignedInteger { bytes: 4 }, byte_order: ByteOrder::LittleEndian }));
+        assert_eq!(parse(b"!d\0"), Some(Scalar { element: Float { bytes: 8 }, byte_order: ByteOrder::BigEndian }));
+        assert_eq!(parse(b"e\0"), Some(Scalar { element: Float { bytes: 2 }, byte_order: ByteOrder::Native }));
+        assert_eq!(parse(b"?\0"), Some(Scalar { element: Bool, byte_order: ByteOrder::Native }));
+        assert_eq!(parse(b">Zd\0"), Some(Scalar { element: Complex { bytes: 16 }, byte_order: ByteOrder::BigEndian }));
+        assert_eq!(parse(b"10s\0"), Some(Bytes { len: 10 }));
+        assert_eq!(parse(b"O\0"), Some(Object));
+        assert_eq!(parse(b"&B\0"), Some(Pointer(Box::new(Scalar { element: UnsignedInteger { bytes: 1 }, byte_order: ByteOrder::Native }))));
+        let array = parse(b"<(2, 3)h\0").unwrap();
+        assert_eq!(array.size(), 12);
+        assert_eq!(array, Array {
+            shape: vec![2, 3],
+            item: Box::new(Scalar { element: SignedInteger { bytes: 2 }, byte_order: ByteOrder::LittleEndian })
+        });
+        assert_eq!(parse(b"1i\0"), parse(b"i\0"));
+
+        assert_eq!(parse(b"\0"), None);
+        assert_eq!(parse(b"Zi\0"), None);
+        assert_eq!(parse(b"T{i\0"), None);
+        assert_eq!(parse(b"g\0"), None);
+        assert_eq!(parse(b"<n\0"), None);
+    }
+
+    #[test]
+    fn test_struct_item_format() {
+        let parse = |s: &'static [u8]| ItemFormat::parse(CStr::from_bytes_with_nul(s).unwrap());
+        let field = |name: Option<&str>, offset, format: &'static [u8]| FormatField {
+            name: name.map(String::from),
+            offset: offset,
+            format: parse(format).unwrap()
+        };
+        // native mode aligns items
+        assert_eq!(parse(b"bxi\0"), Some(ItemFormat::Struct {
+            fields: vec![field(None, 0, b"b\0"), field(None, 4, b"i\0")],
+            size: 8
+        }));
+        // standard mode does not
+        assert_eq!(parse(b"T{<b:a: 2x >d:b:}\0"), Some(ItemFormat::Struct {
+            fields: vec![field(Some("a"), 0, b"<b\0"), field(Some("b"), 3, b">d\0")],
+            size: 11
+        }));
+        assert_eq!(parse(b"T{=h:x:T{=h:y:}:z:}\0").unwrap().size(), 4);
+        // sizes that overflow `usize` are rejected
+        assert_eq!(parse(b"(4294967296,4294967296)q\0"), None);
+        assert_eq!(parse(b"2T{18446744073709551615x}\0"), None);
+        assert_eq!(parse(b"18446744073709551615xb\0"), None);
+        assert_eq!(parse(b"b18446744073709551615x\0"), None);
+        assert_eq!(parse(b"18446744073709551614xi\0"), None);
+    }
+
+    #[test]
+    fn test_endian_element() {
+        use super::Element;
+        let format = |s: &'static [u8]| CStr::from_bytes_with_nul(s).unwrap();
+        assert!(BigEndian::<i32>::is_compatible_format(format(b">i\0")));
+        assert!(BigEndian::<i32>::is_compatible_format(format(b"!i\0")));
+        assert!(!BigEndian::<i32>::is_compatible_format(format(b"<i\0")));
+        assert!(LittleEndian::<f64>::is_compatible_format(format(b"<d\0")));
+        assert!(!LittleEndian::<f64>::is_compatible_format(format(b"<f\0")));
+        assert!(i32::is_compatible_format(format(b"i\0")));
+        assert!(u8::is_compatible_format(format(b"?\0")));
+        assert!(!i8::is_compatible_format(format(b"?\0")));
+        assert!(u8::is_compatible_format(format(b">B\0")));
+        #[cfg(target_endian = "little")]
+        {
+            assert!(LittleEndian::<i32>::is_compatible_format(format(b"i\0")));
+            assert!(!i32::is_compatible_format(format(b">i\0")));
+        }
+
+        assert_eq!(BigEndian::new(0x01020304i32).get(), 0x01020304);
+        assert_eq!(LittleEndian::new(1.5f32).get(), 1.5);
+        assert_eq!(unsafe { ::std::mem::transmute::<_, [u8; 4]>(BigEndian::new(0x01020304u32)) }, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_big_endian_buffer() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let array = py.eval("memoryview((__import__('ctypes').c_int32.__ctype_be__ * 3)(1, -2, 3))", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &array).unwrap();
+        assert_eq!(buffer.format().to_bytes(), b">i");
+        assert_eq!(buffer.item_format(), Some(ItemFormat::Scalar {
+            element: ElementType::SignedInteger { bytes: 4 },
+            byte_order: ByteOrder::BigEndian
+        }));
+        #[cfg(target_endian = "little")]
+        assert!(buffer.to_vec::<i32>(py).is_err());
+        let values: Vec<i32> = buffer.to_vec::<BigEndian<i32>>(py).unwrap().into_iter().map(|v| v.get()).collect();
+        assert_eq!(values, [1, -2, 3]);
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_bool_buffer_as_u8() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = py.eval("memoryview(b'\\x00\\x01\\x02').cast('?')", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &view).unwrap();
+        assert_eq!(buffer.format().to_bytes(), b"?");
+        assert_eq!(buffer.to_vec::<u8>(py).unwrap(), [0, 1, 2]);
+    }
+
+    #[test]
+    #[cfg(feature="num-complex")]
+    fn test_complex_element() {
+        use num_complex::Complex;
+        use super::Element;
+        let format = |s: &'static [u8]| CStr::from_bytes_with_nul(s).unwrap();
+        assert!(Complex::<f64>::is_compatible_format(format(b"Zd\0")));
+        assert!(Complex::<f64>::is_compatible_format(format(b"=Zd\0")));
+        assert!(!Complex::<f64>::is_compatible_format(format(b"Zf\0")));
+        assert!(Complex::<f32>::is_compatible_format(format(b"Zf\0")));
+        assert!(!Complex::<f32>::is_compatible_format(format(b"d\0")));
+    }
 
     #[test]
     fn test_compatible_size() {
@@ -646,5 +1555,120 @@ mod test {
 
         assert_eq!(buffer.to_vec::<f32>(py).unwrap(), [10.0, 11.0, 12.0, 13.0]);
     }
-}
 
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_array_view() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = py.eval("memoryview(__import__('array').array('i', range(12))).cast('B').cast('i', [3, 4])", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &view).unwrap();
+        assert!(buffer.as_array_view::<f32>(py).is_none());
+        let array = buffer.as_array_view::<i32>(py).unwrap();
+        assert_eq!(array.dimensions(), 2);
+        assert_eq!(array.shape(), [3, 4]);
+        assert_eq!(array.item_count(), 12);
+        assert_eq!(array.get(&[1, 2]), Some(6));
+        assert_eq!(array.get(&[3, 0]), None);
+        assert_eq!(array.get(&[1]), None);
+
+        let rows: Vec<Vec<i32>> = array.rows().map(|row| row.to_vec()).collect();
+        assert_eq!(rows, [[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]]);
+        let row = array.row(2).unwrap();
+        assert_eq!(row.dimensions(), 1);
+        assert_eq!(row.get(&[3]), Some(11));
+        assert!(array.row(3).is_none());
+        assert_eq!(row.row(1).unwrap().get(&[]), Some(9));
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_strided_array_view() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = py.eval("memoryview(__import__('array').array('h', range(10)))[::-3]", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &view).unwrap();
+        assert!(!buffer.is_c_contiguous());
+        assert!(buffer.as_slice::<i16>(py).is_none());
+        let array = buffer.as_array_view::<i16>(py).unwrap();
+        assert_eq!(array.strides(), [-6]);
+        assert_eq!(array.get(&[1]), Some(6));
+        assert_eq!(array.to_vec(), [9, 6, 3, 0]);
+        let mut target = [0i16; 4];
+        array.copy_to_slice(&mut target);
+        assert_eq!(target, [9, 6, 3, 0]);
+        assert_eq!(array.rows().len(), 4);
+    }
+
+    #[test]
+    fn test_get_with_flags() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = py.eval("b'abcd'", None, None).unwrap();
+        let err = PyBuffer::get_with_flags(py, &bytes, BufferFlags::WRITABLE).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::BufferError>()));
+
+        let bytearray = py.eval("bytearray(b'abcd')", None, None).unwrap();
+        let buffer = PyBuffer::get_with_flags(py, &bytearray, BufferFlags::SIMPLE | BufferFlags::WRITABLE).unwrap();
+        assert!(!buffer.readonly());
+        assert_eq!(buffer.format().to_bytes(), b"B");
+        assert_eq!(buffer.shape(), [4]);
+        assert_eq!(buffer.strides(), [1]);
+        buffer.copy_from_slice(py, b"efgh").unwrap();
+        drop(buffer);
+        assert_eq!(bytearray.extract::<Vec<u8>>(py).unwrap(), b"efgh");
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_get_with_flags_layout() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = py.eval("memoryview(__import__('array').array('i', range(6))).cast('B').cast('i', [2, 3])", None, None).unwrap();
+        let buffer = PyBuffer::get_with_flags(py, &view, BufferFlags::ND | BufferFlags::FORMAT).unwrap();
+        assert_eq!(buffer.shape(), [2, 3]);
+        assert_eq!(buffer.strides(), [12, 4]);
+        assert!(buffer.is_c_contiguous());
+        assert_eq!(buffer.to_vec::<i32>(py).unwrap(), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(buffer.as_array_view::<i32>(py).unwrap().get(&[1, 0]), Some(3));
+
+        let strided = py.eval("memoryview(b'abcdef')[::2]", None, None).unwrap();
+        let err = PyBuffer::get_with_flags(py, &strided, BufferFlags::C_CONTIGUOUS).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::BufferError>()));
+        let buffer = PyBuffer::get_with_flags(py, &strided, BufferFlags::STRIDES).unwrap();
+        assert_eq!(buffer.strides(), [2]);
+        assert_eq!(buffer.to_vec::<u8>(py).unwrap(), b"ace");
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_with_mut_slice() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let array = py.import("array").unwrap().as_object().call_method(py, "array", ("d", (1.0, 2.0, 3.0)), None).unwrap();
+        let buffer = PyBuffer::get(py, &array).unwrap();
+        let sum = unsafe {
+            buffer.with_mut_slice(py, |slice: &mut [f64]| {
+                py.allow_threads(|| {
+                    for x in slice.iter_mut() {
+                        *x *= 2.0;
+                    }
+                    slice.iter().sum::<f64>()
+                })
+            })
+        }.unwrap();
+        assert_eq!(sum, 12.0);
+        assert_eq!(unsafe { buffer.with_slice(py, |slice: &[f64]| slice.to_vec()) }.unwrap(), [2.0, 4.0, 6.0]);
+        assert!(unsafe { buffer.with_slice(py, |_: &[f32]| ()) }.is_err());
+
+        let bytes = py.eval("b'abc'", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &bytes).unwrap();
+        assert_eq!(unsafe { buffer.with_slice(py, |slice: &[u8]| slice.len()) }.unwrap(), 3);
+        let err = unsafe { buffer.with_mut_slice(py, |_: &mut [u8]| ()) }.err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::BufferError>()));
+
+        let strided = py.eval("memoryview(b'abcdef')[::2]", None, None).unwrap();
+        let buffer = PyBuffer::get(py, &strided).unwrap();
+        assert!(unsafe { buffer.with_slice(py, |_: &[u8]| ()) }.is_err());
+    }
+}
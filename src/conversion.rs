@@ -19,8 +19,8 @@
 use std;
 use ffi;
 use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PyDrop, PyClone};
-use objects::PyObject;
-use err::PyResult;
+use objects::{PyObject, exc};
+use err::{PyErr, PyResult};
 
 /// Conversion trait that allows various objects to be converted into Python objects.
 pub trait ToPyObject {
@@ -165,6 +165,60 @@ impl <'a, T: ?Sized> ToPyObject for &'a T where T: ToPyObject {
     }
 }
 
+macro_rules! smart_pointer_to_py_object {
+    ($($ptr:ident)::+) => {
+        /// Converts the pointee to a Python object.
+        impl <T: ?Sized> ToPyObject for $($ptr)::+<T> where T: ToPyObject {
+            type ObjectType = T::ObjectType;
+
+            #[inline]
+            fn to_py_object(&self, py: Python) -> T::ObjectType {
+                <T as ToPyObject>::to_py_object(&**self, py)
+            }
+
+            #[inline]
+            fn with_borrowed_ptr<F, R>(&self, py: Python, f: F) -> R
+                where F: FnOnce(*mut ffi::PyObject) -> R
+            {
+                <T as ToPyObject>::with_borrowed_ptr(&**self, py, f)
+            }
+        }
+    }
+}
+
+smart_pointer_to_py_object!(Box);
+smart_pointer_to_py_object!(std::rc::Rc);
+smart_pointer_to_py_object!(std::sync::Arc);
+
+impl <'source, T> FromPyObject<'source> for Box<T> where T: FromPyObject<'source> {
+    #[inline]
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        T::extract(py, obj).map(Box::new)
+    }
+}
+
+/// `()` is converted to Python `None`.
+impl ToPyObject for () {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        py.None()
+    }
+}
+
+/// Returns `Ok(())` if the input is Python `None`.
+/// Otherwise, returns a `TypeError`.
+impl <'source> FromPyObject<'source> for () {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        if obj.as_ptr() == unsafe { ffi::Py_None() } {
+            Ok(())
+        } else {
+            Err(PyErr::new::<exc::TypeError, _>(py, "expected None"))
+        }
+    }
+}
+
 /// `Option::Some<T>` is converted like `T`.
 /// `Option::None` is converted to Python `None`.
 impl <T> ToPyObject for Option<T> where T: ToPyObject {
@@ -226,3 +280,32 @@ where T: ExtractPyObject<'prepared>
 }
 */
 
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+
+    #[test]
+    fn test_smart_pointers() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(Box::new(5i32).to_py_object(py).into_object().extract::<i32>(py).unwrap(), 5);
+        assert_eq!(Rc::new("a").to_py_object(py).into_object().extract::<String>(py).unwrap(), "a");
+        assert_eq!(Arc::new(vec![1]).to_py_object(py).into_object().extract::<Box<Vec<i32>>>(py).unwrap(), Box::new(vec![1]));
+        let boxed: Box<str> = "xyz".into();
+        assert_eq!(boxed.to_py_object(py).into_object().extract::<String>(py).unwrap(), "xyz");
+    }
+
+    #[test]
+    fn test_unit() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let none = ().to_py_object(py);
+        assert!(none == py.None());
+        assert!(none.extract::<()>(py).is_ok());
+        assert!(5i32.to_py_object(py).into_object().extract::<()>(py).is_err());
+    }
+}
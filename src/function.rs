@@ -26,26 +26,155 @@ use conversion::ToPyObject;
 use ffi;
 use err::{self, PyResult};
 
-#[macro_export]
+#[macro_export(local_inner_macros)]
 #[doc(hidden)]
 macro_rules! py_method_def {
-    ($name: expr, $flags: expr, $wrap: expr) => {{
+    ($name: expr, $flags: expr, $wrap: expr) => {
+        py_method_def!($name,
+            $crate::_detail::ffi::METH_VARARGS | $crate::_detail::ffi::METH_KEYWORDS | $flags,
+            $wrap, $crate::_detail::ffi::PyCFunctionWithKeywords)
+    };
+    ($name: expr, $flags: expr, $wrap: expr, $wrap_type: ty) => {{
         static mut METHOD_DEF: $crate::_detail::ffi::PyMethodDef = $crate::_detail::ffi::PyMethodDef {
             //ml_name: bytes!(stringify!($name), "\0"),
             ml_name: 0 as *const $crate::_detail::libc::c_char,
             ml_meth: None,
-            ml_flags: $crate::_detail::ffi::METH_VARARGS | $crate::_detail::ffi::METH_KEYWORDS | $flags,
+            ml_flags: $flags,
             ml_doc: 0 as *const $crate::_detail::libc::c_char
         };
-        METHOD_DEF.ml_name = concat!($name, "\0").as_ptr() as *const _;
+        METHOD_DEF.ml_name = _cpython__function__concat!($name, "\0").as_ptr() as *const _;
         METHOD_DEF.ml_meth = Some(
-            ::std::mem::transmute::<$crate::_detail::ffi::PyCFunctionWithKeywords,
-                                  $crate::_detail::ffi::PyCFunction>($wrap)
+            ::std::mem::transmute::<$wrap_type, $crate::_detail::ffi::PyCFunction>($wrap)
         );
         &mut METHOD_DEF
     }}
 }
 
+// Expands to a `*mut PyMethodDef` for a function with the given parameter list,
+// picking the cheapest calling convention that can handle the parameter list:
+//  * `METH_NOARGS` if there are no parameters
+//  * `METH_O` for a single required positional-only parameter
+//  * `METH_VARARGS | METH_KEYWORDS` for `(*args, **kwargs)`
+//  * otherwise see `py_method_def_with_keywords!`
+//
+// Usage: `py_method_def_for_params!(name, flags, location, |py, slf| [plist] { body })`
+// `slf` is bound to the `self` pointer passed by Python (or the class for METH_CLASS).
+// Must be used in an unsafe block.
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_method_def_for_params {
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident| [] $body:block) => {{
+        unsafe extern "C" fn wrap(
+            $slf: *mut $crate::_detail::ffi::PyObject,
+            _args: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            $crate::_detail::handle_callback(
+                $location, $crate::_detail::PyObjectCallbackConverter,
+                |$py| $body)
+        }
+        py_method_def!($name, $crate::_detail::ffi::METH_NOARGS | $flags,
+            wrap, $crate::_detail::ffi::PyCFunction)
+    }};
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident|
+        [ { $pname:ident : $ptype:ty = [ {/} {} $rtype:tt ] } ]
+        $body:block
+    ) => {{
+        unsafe extern "C" fn wrap(
+            $slf: *mut $crate::_detail::ffi::PyObject,
+            arg: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            $crate::_detail::handle_callback(
+                $location, $crate::_detail::PyObjectCallbackConverter,
+                |$py| {
+                    let output = [Some($crate::PyObject::borrow_from_ptr(&arg))];
+                    let mut _iter = output.iter();
+                    py_argparse_extract!($py, Some($location), _iter, $body,
+                        [ { $pname : $ptype = [ {/} {} $rtype ] } ])
+                })
+        }
+        py_method_def!($name, $crate::_detail::ffi::METH_O | $flags,
+            wrap, $crate::_detail::ffi::PyCFunction)
+    }};
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident|
+        [
+            { $pargs:ident   : $pargs_type:ty   = [ {*}  {} {} ] }
+            { $pkwargs:ident : $pkwargs_type:ty = [ {**} {} {} ] }
+        ]
+        $body:block
+    ) => {
+        py_method_def_varargs!($name, $flags, $location, |$py, $slf|
+            [
+                { $pargs   : $pargs_type   = [ {*}  {} {} ] }
+                { $pkwargs : $pkwargs_type = [ {**} {} {} ] }
+            ]
+            $body)
+    };
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident| $plist:tt $body:block) => {
+        py_method_def_with_keywords!($name, $flags, $location, |$py, $slf| $plist $body)
+    };
+}
+
+// Like py_method_def_for_params!(), but always uses `METH_VARARGS | METH_KEYWORDS`.
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_method_def_varargs {
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident| $plist:tt $body:block) => {{
+        unsafe extern "C" fn wrap(
+            $slf: *mut $crate::_detail::ffi::PyObject,
+            args: *mut $crate::_detail::ffi::PyObject,
+            kwargs: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            const LOCATION: &'static str = $location;
+            $crate::_detail::handle_callback(
+                LOCATION, $crate::_detail::PyObjectCallbackConverter,
+                |$py| {
+                    py_argparse_raw!($py, Some(LOCATION), args, kwargs, $plist $body)
+                })
+        }
+        py_method_def!($name, $flags, wrap)
+    }};
+}
+
+// Python 3.7+: use `METH_FASTCALL | METH_KEYWORDS`, which passes the arguments
+// as a C array instead of allocating an argument tuple and keyword dict.
+#[cfg(Py_3_7)]
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_method_def_with_keywords {
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident| $plist:tt $body:block) => {{
+        unsafe extern "C" fn wrap(
+            $slf: *mut $crate::_detail::ffi::PyObject,
+            args: *const *mut $crate::_detail::ffi::PyObject,
+            nargs: $crate::_detail::ffi::Py_ssize_t,
+            kwnames: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            const LOCATION: &'static str = $location;
+            $crate::_detail::handle_callback(
+                LOCATION, $crate::_detail::PyObjectCallbackConverter,
+                |$py| {
+                    py_argparse_fastcall_raw!($py, Some(LOCATION), args, nargs, kwnames, $plist $body)
+                })
+        }
+        py_method_def!($name,
+            $crate::_detail::ffi::METH_FASTCALL | $crate::_detail::ffi::METH_KEYWORDS | $flags,
+            wrap, $crate::_detail::ffi::_PyCFunctionFastWithKeywords)
+    }};
+}
+
+// Older Python versions: fall back to `METH_VARARGS | METH_KEYWORDS`.
+#[cfg(not(Py_3_7))]
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_method_def_with_keywords {
+    ($name:expr, $flags:expr, $location:expr, |$py:ident, $slf:ident| $plist:tt $body:block) => {
+        py_method_def_varargs!($name, $flags, $location, |$py, $slf| $plist $body)
+    };
+}
+
 /// Creates a Python callable object that invokes a Rust function.
 ///
 /// There are two forms of this macro:
@@ -112,25 +241,14 @@ macro_rules! py_fn {
 macro_rules! py_fn_impl {
     // Form 1: reference existing function
     { $py:expr, $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] } => {{
-        unsafe extern "C" fn wrap(
-            _slf: *mut $crate::_detail::ffi::PyObject,
-            args: *mut $crate::_detail::ffi::PyObject,
-            kwargs: *mut $crate::_detail::ffi::PyObject)
-        -> *mut $crate::_detail::ffi::PyObject
-        {
-            $crate::_detail::handle_callback(
-                _cpython__function__stringify!($f), $crate::_detail::PyObjectCallbackConverter,
-                |py| {
-                    py_argparse_raw!(py, Some(_cpython__function__stringify!($f)), args, kwargs,
-                        [ $( { $pname : $ptype = $detail } )* ]
-                        {
-                            $f(py $(, $pname )* )
-                        })
-                })
-        }
         unsafe {
             $crate::_detail::py_fn_impl($py,
-                py_method_def!(_cpython__function__stringify!($f), 0, wrap))
+                py_method_def_for_params!(_cpython__function__stringify!($f), 0,
+                    _cpython__function__stringify!($f),
+                    |py, _slf| [ $( { $pname : $ptype = $detail } )* ]
+                    {
+                        $f(py $(, $pname )* )
+                    }))
         }
     }};
     // Form 2: inline function definition
@@ -144,6 +262,171 @@ pub unsafe fn py_fn_impl(py: Python, method_def: *mut ffi::PyMethodDef) -> PyObj
     err::from_owned_ptr_or_panic(py, ffi::PyCFunction_New(method_def, ptr::null_mut()))
 }
 
+/// Creates a Python callable object that invokes a Rust closure.
+///
+/// The closure receives the positional arguments as a tuple and the keyword
+/// arguments (if any) as a dict.
+/// `name` is used as the `__name__` of the function object and in error messages;
+/// it must not contain NUL bytes.
+///
+/// The returned object owns the closure; the closure is dropped
+/// when the Python object is deallocated.
+/// Use the `py_closure!` macro to parse the arguments into typed parameters.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, ObjectProtocol, py_fn_closure};
+/// use std::sync::mpsc;
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let (sender, receiver) = mpsc::channel();
+/// let callback = py_fn_closure(py, "callback", move |py, args, _kwargs| {
+///     sender.send(args.len(py)).unwrap();
+///     Ok(py.None())
+/// });
+/// callback.call(py, (1, 2, 3), None).unwrap();
+/// assert_eq!(receiver.recv().unwrap(), 3);
+/// ```
+pub fn py_fn_closure<F>(py: Python, name: &str, f: F) -> PyObject
+    where F: Fn(Python, &PyTuple, Option<&PyDict>) -> PyResult<PyObject> + Send + 'static
+{
+    let name = CString::new(name).expect("closure name must not contain NUL bytes");
+    let mut data = Box::new(ClosureData {
+        def: ffi::PyMethodDef {
+            ml_name: name.as_ptr(),
+            ml_meth: None,
+            ml_flags: ffi::METH_VARARGS | ffi::METH_KEYWORDS,
+            ml_doc: ptr::null()
+        },
+        name: name,
+        f: Box::new(f)
+    });
+    unsafe {
+        data.def.ml_meth = Some(mem::transmute::<ffi::PyCFunctionWithKeywords, ffi::PyCFunction>(closure_wrap));
+        let def: *mut ffi::PyMethodDef = &mut data.def;
+        let name = data.name.as_ptr();
+        // The capsule owns the closure data. It is stored in a module object, which the
+        // function object keeps alive as `self`: with a module as `self`, the function
+        // looks like a plain built-in function (e.g. in `repr()`).
+        let capsule = err::from_owned_ptr_or_panic(py, ffi::PyCapsule_New(
+            Box::into_raw(data) as *mut libc::c_void,
+            CLOSURE_CAPSULE_NAME.as_ptr() as *const _,
+            Some(closure_capsule_destructor)));
+        let module = err::from_owned_ptr_or_panic(py, ffi::PyModule_New(name));
+        let ret = ffi::PyDict_SetItemString(ffi::PyModule_GetDict(module.as_ptr()),
+            CLOSURE_CAPSULE_NAME.as_ptr() as *const _, capsule.as_ptr());
+        capsule.release_ref(py);
+        if ret != 0 {
+            err::PyErr::fetch(py).print(py);
+            panic!("failed to store the closure in its module");
+        }
+        let func = ffi::PyCFunction_New(def, module.as_ptr());
+        module.release_ref(py);
+        err::from_owned_ptr_or_panic(py, func)
+    }
+}
+
+struct ClosureData {
+    // `def` and `name` must stay at a fixed address while the function object exists,
+    // so ClosureData is always boxed.
+    def: ffi::PyMethodDef,
+    name: CString,
+    f: Box<Fn(Python, &PyTuple, Option<&PyDict>) -> PyResult<PyObject> + Send>
+}
+
+const CLOSURE_CAPSULE_NAME: &'static [u8] = b"cpython.function.closure\0";
+
+unsafe extern "C" fn closure_wrap(
+    module: *mut ffi::PyObject,
+    args: *mut ffi::PyObject,
+    kwargs: *mut ffi::PyObject)
+-> *mut ffi::PyObject
+{
+    let capsule = ffi::PyDict_GetItemString(ffi::PyModule_GetDict(module), CLOSURE_CAPSULE_NAME.as_ptr() as *const _);
+    // Fails with `ValueError` if Python code removed the capsule from the module.
+    let data = ffi::PyCapsule_GetPointer(capsule, CLOSURE_CAPSULE_NAME.as_ptr() as *const _) as *const ClosureData;
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    // Keep the closure alive during the call, even if it removes the capsule from the module.
+    ffi::Py_INCREF(capsule);
+    let location = (*data).name.to_str().unwrap_or("<closure>");
+    let f = panic::AssertUnwindSafe(&(*data).f);
+    let ret = handle_callback(location, PyObjectCallbackConverter, |py| {
+        let args: PyTuple = PyObject::from_borrowed_ptr(py, args).unchecked_cast_into();
+        let kwargs: Option<PyDict> = ::argparse::get_kwargs(py, kwargs);
+        let ret = (f.0)(py, &args, kwargs.as_ref());
+        args.release_ref(py);
+        kwargs.release_ref(py);
+        ret
+    });
+    ffi::Py_DECREF(capsule);
+    ret
+}
+
+unsafe extern "C" fn closure_capsule_destructor(capsule: *mut ffi::PyObject) {
+    let data = ffi::PyCapsule_GetPointer(capsule, CLOSURE_CAPSULE_NAME.as_ptr() as *const _) as *mut ClosureData;
+    let guard = AbortOnDrop("closure destructor");
+    drop(Box::from_raw(data));
+    mem::forget(guard);
+}
+
+/// Creates a Python callable object that invokes a Rust closure body.
+///
+/// Syntax: `py_closure!(py, f(parameter_list) -> PyResult<T> { body })`
+///
+/// This works like the second form of `py_fn!()`, except that the body
+/// may capture variables from the enclosing scope. Captured variables are moved into
+/// the Python object and dropped when it is deallocated, so they must be `Send + 'static`.
+///
+///  * `py` must be an identifier referring to a `Python` value.
+///    The body will also have access to a `Python` variable of this name.
+///  * `f` must be an identifier; it is used as the function name in error messages.
+///  * The return type must be `PyResult<T>` for some `T` that implements `ToPyObject`.
+///
+/// See `py_argparse!()` for details on argument parsing.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyResult, PyDict};
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let offset = 10;
+///     let add_offset = py_closure!(py, add_offset(x: i32) -> PyResult<i32> {
+///         Ok(x + offset)
+///     });
+///     let dict = PyDict::new(py);
+///     dict.set_item(py, "add_offset", add_offset).unwrap();
+///     py.run("assert add_offset(32) == 42", None, Some(&dict)).unwrap();
+/// }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! py_closure {
+    ($py:ident, $f:ident $plist:tt -> $ret:ty { $($body:tt)* } ) => {
+        py_argparse_parse_plist! { py_closure_impl { $py, $f, $ret, { $($body)* } } $plist }
+    };
+}
+
+#[macro_export(local_inner_macros)]
+#[doc(hidden)]
+macro_rules! py_closure_impl {
+    { $py:ident, $f:ident, $ret:ty, $body:block [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] } => {
+        $crate::py_fn_closure($py, _cpython__function__stringify!($f),
+            move |$py: $crate::Python, args: &$crate::PyTuple, kwargs: Option<&$crate::PyDict>|
+                -> $crate::PyResult<$crate::PyObject>
+            {
+                py_argparse_impl!($py, Some(_cpython__function__stringify!($f)), args, kwargs, {
+                    let ret: $ret = $body;
+                    ret.map(|val| $crate::PythonObject::into_object($crate::ToPyObject::into_py_object(val, $py)))
+                }, [ $( { $pname : $ptype = $detail } )* ])
+            })
+    }
+}
+
 pub trait CallbackConverter<S> {
     type R;
 
@@ -241,4 +524,12 @@ macro_rules! _cpython__function__stringify {
     }
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _cpython__function__concat {
+    ($($inner:tt)*) => {
+        concat! { $($inner)* }
+    }
+}
+
 // Tests for this file are in tests/test_function.rs
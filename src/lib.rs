@@ -90,6 +90,13 @@
 
 extern crate libc;
 
+#[cfg(feature="num-complex")]
+extern crate num_complex;
+#[cfg(feature="num-bigint")]
+extern crate num_bigint;
+#[cfg(feature="chrono")]
+extern crate chrono;
+
 #[cfg(feature="python27-sys")]
 extern crate python27_sys as ffi;
 
@@ -104,6 +111,7 @@ pub use pythonrun::{GILGuard, GILProtected, prepare_freethreaded_python};
 pub use conversion::{FromPyObject, RefFromPyObject, ToPyObject};
 pub use py_class::{CompareOp};
 pub use objectprotocol::{ObjectProtocol};
+pub use function::py_fn_closure;
 
 #[cfg(feature="python27-sys")]
 #[allow(non_camel_case_types)]
@@ -221,6 +221,67 @@ pub trait ObjectProtocol : PythonObject {
         self.getattr(py, name)?.call(py, args, kwargs)
     }
 
+    /// Calls the object using the vectorcall protocol (PEP 590).
+    /// This is equivalent to the Python expression: 'self(*positional, **keywords)',
+    /// where the keyword argument names are taken from `kwnames`.
+    ///
+    /// `args` holds the positional arguments, followed by the values of the keyword
+    /// arguments. `kwnames` is a tuple of strings naming the keyword arguments;
+    /// its length determines how many entries at the end of `args` are keyword values.
+    ///
+    /// Unlike `call()`, this does not need to allocate an argument tuple or keyword
+    /// dictionary when the callee supports vectorcall, which makes it suitable for
+    /// calling Python callbacks in hot loops.
+    /// The `kwnames` tuple can be created once and reused across calls.
+    ///
+    /// Panics if `kwnames` has more entries than `args`.
+    ///
+    /// Requires Python 3.8 or later.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cpython::{ObjectProtocol, PyTuple, PyString, PythonObject, ToPyObject};
+    /// # use cpython::Python;
+    /// # let gil = Python::acquire_gil();
+    /// # let py = gil.python();
+    /// # let callback = py.None();
+    /// let kwnames = PyTuple::new(py, &[PyString::new(py, "sep").into_object()]);
+    /// // callback(1, 2, sep="-")
+    /// let args = [1i32.to_py_object(py).into_object(),
+    ///             2i32.to_py_object(py).into_object(),
+    ///             "-".to_py_object(py).into_object()];
+    /// callback.vectorcall(py, &args, Some(&kwnames)).unwrap();
+    /// ```
+    #[cfg(Py_3_8)]
+    #[inline]
+    fn vectorcall(&self, py: Python, args: &[PyObject], kwnames: Option<&PyTuple>) -> PyResult<PyObject> {
+        let nkwargs = match kwnames {
+            Some(kwnames) => kwnames.len(py),
+            None => 0
+        };
+        assert!(nkwargs <= args.len(), "vectorcall: more keyword names than arguments");
+        let nargs = (args.len() - nkwargs) as libc::size_t;
+        // PyObject is a #[repr(C)] wrapper around a single object pointer,
+        // so a slice of PyObject has the layout of a `PyObject *const *` array.
+        let args_ptr = args.as_ptr() as *const *mut ffi::PyObject;
+        unsafe {
+            err::result_from_owned_ptr(py,
+                ffi::PyObject_Vectorcall(self.as_ptr(), args_ptr, nargs, kwnames.as_ptr()))
+        }
+    }
+
+    /// Calls a method on the object using the vectorcall protocol (PEP 590).
+    /// This is equivalent to the Python expression: 'self.name(*positional, **keywords)'.
+    ///
+    /// See `vectorcall()` for the meaning of `args` and `kwnames`.
+    ///
+    /// Requires Python 3.8 or later.
+    #[cfg(Py_3_8)]
+    #[inline]
+    fn vectorcall_method(&self, py: Python, name: &str, args: &[PyObject], kwnames: Option<&PyTuple>) -> PyResult<PyObject> {
+        self.getattr(py, name)?.vectorcall(py, args, kwnames)
+    }
+
     /// Retrieves the hash code of the object.
     /// This is equivalent to the Python expression: 'hash(self)'
     #[inline]
@@ -355,5 +416,34 @@ mod test {
         assert_eq!(one.compare(py, 2).unwrap(), Ordering::Less);
         assert_eq!(one.compare(py, 0).unwrap(), Ordering::Greater);
     }
+
+    #[test]
+    #[cfg(Py_3_8)]
+    fn test_vectorcall() {
+        use objects::{PyDict, PyString};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        let f = py.eval("lambda *args, **kwargs: (args, sorted(kwargs.items()))", None, Some(&d)).unwrap();
+        let args = [1i32.to_py_object(py).into_object(), 2i32.to_py_object(py).into_object()];
+        let result = f.vectorcall(py, &args, None).unwrap();
+        assert_eq!(format!("{:?}", result), "((1, 2), [])");
+        let kwnames = PyTuple::new(py, &[PyString::new(py, "b").into_object()]);
+        let result = f.vectorcall(py, &args, Some(&kwnames)).unwrap();
+        assert_eq!(format!("{:?}", result), "((1,), [('b', 2)])");
+        let result = f.vectorcall(py, &[], None).unwrap();
+        assert_eq!(format!("{:?}", result), "((), [])");
+    }
+
+    #[test]
+    #[cfg(Py_3_8)]
+    fn test_vectorcall_method() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::new(py, &[]).into_object();
+        let args = [5i32.to_py_object(py).into_object()];
+        list.vectorcall_method(py, "append", &args, None).unwrap();
+        assert_eq!(format!("{:?}", list), "[5]");
+    }
 }
 
@@ -0,0 +1,202 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::mem;
+use std::ffi::CStr;
+use libc::c_void;
+use ffi;
+use python::Python;
+use objects::PyObject;
+use function::AbortOnDrop;
+use err::{self, PyResult, PyErr};
+
+/// Represents a Python capsule object.
+///
+/// Capsules wrap an opaque pointer together with a name.
+/// They are used to share data and C APIs between extension modules:
+/// one module stores a capsule as a module attribute, and other modules
+/// retrieve the pointer with `PyCapsule::import()`.
+///
+/// Retrieving the pointer requires passing the same name that the capsule
+/// was created with; by convention, this is the dotted path of the
+/// module attribute holding the capsule (e.g. `"mymodule._C_API"`).
+pub struct PyCapsule(PyObject);
+
+pyobject_newtype!(PyCapsule, PyCapsule_CheckExact, PyCapsule_Type);
+
+impl PyCapsule {
+    /// Creates a new capsule that owns `value`.
+    ///
+    /// `value` is dropped when the capsule is deallocated.
+    pub fn new<T>(py: Python, value: Box<T>, name: &'static CStr) -> PyResult<PyCapsule>
+        where T: Send + 'static
+    {
+        let pointer = Box::into_raw(value);
+        unsafe {
+            let capsule = ffi::PyCapsule_New(pointer as *mut c_void, name.as_ptr(),
+                Some(capsule_destructor::<T>));
+            if capsule.is_null() {
+                // The capsule wasn't created, so we still own the value.
+                drop(Box::from_raw(pointer));
+            }
+            err::result_cast_from_owned_ptr(py, capsule)
+        }
+    }
+
+    /// Creates a new capsule that points to static data.
+    ///
+    /// This is the usual way to export a table of functions.
+    pub fn new_static<T>(py: Python, value: &'static T, name: &'static CStr) -> PyResult<PyCapsule>
+        where T: Sync
+    {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyCapsule_New(
+                value as *const T as *mut c_void, name.as_ptr(), None))
+        }
+    }
+
+    /// Imports the capsule stored in a module attribute, and returns a reference to its data.
+    ///
+    /// `name` is the dotted path of the attribute, e.g. `"mymodule._C_API"`;
+    /// it must also be the name the capsule was created with.
+    ///
+    /// # Safety
+    /// The capsule must contain a `T`.
+    /// The returned reference is valid as long as the capsule is alive; this is usually the
+    /// case for the lifetime of the process, because imported modules are kept in `sys.modules`.
+    pub unsafe fn import<T>(py: Python, name: &CStr) -> PyResult<&'static T> {
+        let pointer = ffi::PyCapsule_Import(name.as_ptr(), 0);
+        if pointer.is_null() {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(&*(pointer as *const T))
+        }
+    }
+
+    /// Gets the name of the capsule.
+    pub fn name(&self, _py: Python) -> Option<&CStr> {
+        unsafe {
+            let name = ffi::PyCapsule_GetName(self.0.as_ptr());
+            if name.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(name))
+            }
+        }
+    }
+
+    /// Checks whether the capsule is valid and has the specified name.
+    pub fn is_valid(&self, _py: Python, name: &CStr) -> bool {
+        unsafe { ffi::PyCapsule_IsValid(self.0.as_ptr(), name.as_ptr()) != 0 }
+    }
+
+    /// Gets the pointer stored in the capsule.
+    ///
+    /// Fails with `ValueError` if `name` doesn't match the name of the capsule.
+    pub fn pointer(&self, py: Python, name: &CStr) -> PyResult<*mut c_void> {
+        unsafe {
+            let pointer = ffi::PyCapsule_GetPointer(self.0.as_ptr(), name.as_ptr());
+            if pointer.is_null() {
+                Err(PyErr::fetch(py))
+            } else {
+                Ok(pointer)
+            }
+        }
+    }
+
+    /// Gets a reference to the data stored in the capsule.
+    ///
+    /// Fails with `ValueError` if `name` doesn't match the name of the capsule.
+    ///
+    /// # Safety
+    /// The capsule must contain a `T`.
+    /// Capsule names are not unique, so checking the name alone cannot guarantee this.
+    pub unsafe fn data_ref<T>(&self, py: Python, name: &CStr) -> PyResult<&T> {
+        self.pointer(py, name).map(|pointer| &*(pointer as *const T))
+    }
+}
+
+unsafe extern "C" fn capsule_destructor<T>(capsule: *mut ffi::PyObject) {
+    let pointer = ffi::PyCapsule_GetPointer(capsule, ffi::PyCapsule_GetName(capsule));
+    let guard = AbortOnDrop("PyCapsule destructor");
+    drop(Box::from_raw(pointer as *mut T));
+    mem::forget(guard);
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::CStr;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use python::{Python, PythonObject};
+    use objects::{PyModule, exc};
+    use objectprotocol::ObjectProtocol;
+    use super::PyCapsule;
+
+    fn name(s: &'static [u8]) -> &'static CStr {
+        CStr::from_bytes_with_nul(s).unwrap()
+    }
+
+    #[test]
+    fn test_data_ref() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let capsule = PyCapsule::new(py, Box::new(42u32), name(b"test.data\0")).unwrap();
+        assert_eq!(capsule.name(py), Some(name(b"test.data\0")));
+        assert!(capsule.is_valid(py, name(b"test.data\0")));
+        assert!(!capsule.is_valid(py, name(b"test.other\0")));
+        assert_eq!(unsafe { *capsule.data_ref::<u32>(py, name(b"test.data\0")).unwrap() }, 42);
+        let err = unsafe { capsule.data_ref::<u32>(py, name(b"test.other\0")) }.unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+    }
+
+    #[test]
+    fn test_drop() {
+        struct DropFlag(Arc<AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let capsule = PyCapsule::new(py, Box::new(DropFlag(dropped.clone())), name(b"test.drop\0")).unwrap();
+        assert!(!dropped.load(Ordering::Relaxed));
+        drop(capsule);
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_import() {
+        static API: (u32, u32) = (1, 2);
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let module = PyModule::new(py, "capsule_test_module").unwrap();
+        let capsule = PyCapsule::new_static(py, &API, name(b"capsule_test_module.API\0")).unwrap();
+        module.add(py, "API", capsule).unwrap();
+        let modules = py.import("sys").unwrap().get(py, "modules").unwrap();
+        modules.set_item(py, "capsule_test_module", module.as_object()).unwrap();
+
+        let api = unsafe { PyCapsule::import::<(u32, u32)>(py, name(b"capsule_test_module.API\0")) }.unwrap();
+        assert_eq!(*api, (1, 2));
+        assert!(unsafe { PyCapsule::import::<(u32, u32)>(py, name(b"capsule_test_module.Missing\0")) }.is_err());
+    }
+}
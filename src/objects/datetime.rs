@@ -0,0 +1,757 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Wrappers for the types of the Python `datetime` module.
+//!
+//! The datetime C API is imported on first use.
+
+use std::ptr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use libc::c_int;
+use ffi;
+use ffi::datetime::*;
+use python::{Python, PythonObject, PyDrop, PyClone, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PythonObjectDowncastError};
+use conversion::{ToPyObject, FromPyObject};
+use objects::{PyObject, PyType, exc};
+use err::{self, PyResult, PyErr};
+
+fn datetime_api(py: Python) -> &'static PyDateTime_CAPI {
+    unsafe {
+        if PyDateTimeAPI.is_null() {
+            PyDateTime_IMPORT();
+            if PyDateTimeAPI.is_null() {
+                PyErr::fetch(py).print(py);
+                panic!("failed to import the datetime C API");
+            }
+        }
+        &*PyDateTimeAPI
+    }
+}
+
+macro_rules! datetime_newtype {
+    ($name: ident, $checkfunction: ident, $typefield: ident) => {
+        pyobject_newtype!($name);
+
+        impl PythonObjectWithCheckedDowncast for $name {
+            #[inline]
+            fn downcast_from<'p>(py: Python<'p>, obj: PyObject) -> Result<$name, PythonObjectDowncastError<'p>> {
+                datetime_api(py);
+                unsafe {
+                    if $checkfunction(obj.as_ptr()) != 0 {
+                        Ok($name(obj))
+                    } else {
+                        Err(PythonObjectDowncastError(py))
+                    }
+                }
+            }
+
+            #[inline]
+            fn downcast_borrow_from<'a, 'p>(py: Python<'p>, obj: &'a PyObject) -> Result<&'a $name, PythonObjectDowncastError<'p>> {
+                datetime_api(py);
+                unsafe {
+                    if $checkfunction(obj.as_ptr()) != 0 {
+                        Ok(::std::mem::transmute(obj))
+                    } else {
+                        Err(PythonObjectDowncastError(py))
+                    }
+                }
+            }
+        }
+
+        impl PythonObjectWithTypeObject for $name {
+            #[inline]
+            fn type_object(py: Python) -> PyType {
+                unsafe { PyType::from_type_ptr(py, datetime_api(py).$typefield) }
+            }
+        }
+    }
+}
+
+/// Represents a Python `datetime.date`.
+pub struct PyDate(PyObject);
+
+datetime_newtype!(PyDate, PyDate_Check, DateType);
+
+/// Represents a Python `datetime.datetime`.
+///
+/// Every `datetime` is also a `date`, so a `PyDateTime` can be cast to `PyDate`.
+pub struct PyDateTime(PyObject);
+
+datetime_newtype!(PyDateTime, PyDateTime_Check, DateTimeType);
+
+/// Represents a Python `datetime.time`.
+pub struct PyTime(PyObject);
+
+datetime_newtype!(PyTime, PyTime_Check, TimeType);
+
+/// Represents a Python `datetime.timedelta`.
+pub struct PyDelta(PyObject);
+
+datetime_newtype!(PyDelta, PyDelta_Check, DeltaType);
+
+/// Represents a Python `datetime.tzinfo`.
+pub struct PyTzInfo(PyObject);
+
+datetime_newtype!(PyTzInfo, PyTZInfo_Check, TZInfoType);
+
+fn opt_tzinfo_ptr(tzinfo: Option<&PyTzInfo>) -> *mut ffi::PyObject {
+    match tzinfo {
+        Some(tzinfo) => tzinfo.as_object().as_ptr(),
+        None => unsafe { ffi::Py_None() }
+    }
+}
+
+unsafe fn tzinfo_from_borrowed_ptr(py: Python, ptr: *mut ffi::PyObject) -> Option<PyTzInfo> {
+    if ptr == ffi::Py_None() {
+        None
+    } else {
+        Some(PyObject::from_borrowed_ptr(py, ptr).unchecked_cast_into())
+    }
+}
+
+impl PyDate {
+    /// Creates a new `datetime.date`.
+    ///
+    /// Fails with `ValueError` if the arguments are out of range.
+    pub fn new(py: Python, year: i32, month: u8, day: u8) -> PyResult<PyDate> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Date_FromDate)(year as c_int, month as c_int, day as c_int, api.DateType))
+        }
+    }
+
+    /// Creates the local date corresponding to a POSIX timestamp.
+    ///
+    /// Corresponds to `date.fromtimestamp(timestamp)` in Python.
+    pub fn from_timestamp(py: Python, timestamp: f64) -> PyResult<PyDate> {
+        let api = datetime_api(py);
+        let args = (timestamp,).to_py_object(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Date_FromTimestamp)(api.DateType as *mut ffi::PyObject, args.as_object().as_ptr()))
+        }
+    }
+
+    /// Gets the year, in the range `1..=9999`.
+    #[inline]
+    pub fn year(&self, _py: Python) -> i32 {
+        unsafe { PyDateTime_GET_YEAR(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the month, in the range `1..=12`.
+    #[inline]
+    pub fn month(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_GET_MONTH(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the day of the month, in the range `1..=31`.
+    #[inline]
+    pub fn day(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_GET_DAY(self.0.as_ptr()) as u8 }
+    }
+}
+
+impl PyDateTime {
+    /// Creates a new `datetime.datetime`.
+    /// If `tzinfo` is `None`, the datetime is naive.
+    ///
+    /// Fails with `ValueError` if the arguments are out of range.
+    pub fn new(py: Python, year: i32, month: u8, day: u8,
+               hour: u8, minute: u8, second: u8, microsecond: u32,
+               tzinfo: Option<&PyTzInfo>) -> PyResult<PyDateTime> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.DateTime_FromDateAndTime)(
+                    year as c_int, month as c_int, day as c_int,
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    opt_tzinfo_ptr(tzinfo), api.DateTimeType))
+        }
+    }
+
+    /// Creates a new `datetime.datetime` with the given `fold` (0 or 1), which
+    /// disambiguates wall times that occur twice when clocks are turned back.
+    #[cfg(Py_3_6)]
+    pub fn new_with_fold(py: Python, year: i32, month: u8, day: u8,
+                         hour: u8, minute: u8, second: u8, microsecond: u32,
+                         tzinfo: Option<&PyTzInfo>, fold: bool) -> PyResult<PyDateTime> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.DateTime_FromDateAndTimeAndFold)(
+                    year as c_int, month as c_int, day as c_int,
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    opt_tzinfo_ptr(tzinfo), fold as c_int, api.DateTimeType))
+        }
+    }
+
+    /// Creates the datetime corresponding to a POSIX timestamp.
+    /// If `tzinfo` is `None`, the result is a naive datetime in local time.
+    ///
+    /// Corresponds to `datetime.fromtimestamp(timestamp, tzinfo)` in Python.
+    pub fn from_timestamp(py: Python, timestamp: f64, tzinfo: Option<&PyTzInfo>) -> PyResult<PyDateTime> {
+        let api = datetime_api(py);
+        let args = unsafe {
+            (timestamp, PyObject::from_borrowed_ptr(py, opt_tzinfo_ptr(tzinfo))).to_py_object(py)
+        };
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.DateTime_FromTimestamp)(api.DateTimeType as *mut ffi::PyObject,
+                                             args.as_object().as_ptr(), ptr::null_mut()))
+        }
+    }
+
+    /// Gets the year, in the range `1..=9999`.
+    #[inline]
+    pub fn year(&self, _py: Python) -> i32 {
+        unsafe { PyDateTime_GET_YEAR(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the month, in the range `1..=12`.
+    #[inline]
+    pub fn month(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_GET_MONTH(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the day of the month, in the range `1..=31`.
+    #[inline]
+    pub fn day(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_GET_DAY(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the hour, in the range `0..=23`.
+    #[inline]
+    pub fn hour(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_DATE_GET_HOUR(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the minute, in the range `0..=59`.
+    #[inline]
+    pub fn minute(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_DATE_GET_MINUTE(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the second, in the range `0..=59`.
+    #[inline]
+    pub fn second(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_DATE_GET_SECOND(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the microsecond, in the range `0..=999999`.
+    #[inline]
+    pub fn microsecond(&self, _py: Python) -> u32 {
+        unsafe { PyDateTime_DATE_GET_MICROSECOND(self.0.as_ptr()) as u32 }
+    }
+
+    /// Gets the `fold` attribute.
+    #[cfg(Py_3_6)]
+    #[inline]
+    pub fn fold(&self, _py: Python) -> bool {
+        unsafe { PyDateTime_DATE_GET_FOLD(self.0.as_ptr()) != 0 }
+    }
+
+    /// Gets the time zone, or `None` if the datetime is naive.
+    #[inline]
+    pub fn tzinfo(&self, py: Python) -> Option<PyTzInfo> {
+        unsafe { tzinfo_from_borrowed_ptr(py, PyDateTime_DATE_GET_TZINFO(self.0.as_ptr())) }
+    }
+}
+
+impl PyTime {
+    /// Creates a new `datetime.time`.
+    /// If `tzinfo` is `None`, the time is naive.
+    ///
+    /// Fails with `ValueError` if the arguments are out of range.
+    pub fn new(py: Python, hour: u8, minute: u8, second: u8, microsecond: u32,
+               tzinfo: Option<&PyTzInfo>) -> PyResult<PyTime> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Time_FromTime)(
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    opt_tzinfo_ptr(tzinfo), api.TimeType))
+        }
+    }
+
+    /// Creates a new `datetime.time` with the given `fold` (0 or 1).
+    #[cfg(Py_3_6)]
+    pub fn new_with_fold(py: Python, hour: u8, minute: u8, second: u8, microsecond: u32,
+                         tzinfo: Option<&PyTzInfo>, fold: bool) -> PyResult<PyTime> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Time_FromTimeAndFold)(
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    opt_tzinfo_ptr(tzinfo), fold as c_int, api.TimeType))
+        }
+    }
+
+    /// Gets the hour, in the range `0..=23`.
+    #[inline]
+    pub fn hour(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_TIME_GET_HOUR(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the minute, in the range `0..=59`.
+    #[inline]
+    pub fn minute(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_TIME_GET_MINUTE(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the second, in the range `0..=59`.
+    #[inline]
+    pub fn second(&self, _py: Python) -> u8 {
+        unsafe { PyDateTime_TIME_GET_SECOND(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the microsecond, in the range `0..=999999`.
+    #[inline]
+    pub fn microsecond(&self, _py: Python) -> u32 {
+        unsafe { PyDateTime_TIME_GET_MICROSECOND(self.0.as_ptr()) as u32 }
+    }
+
+    /// Gets the `fold` attribute.
+    #[cfg(Py_3_6)]
+    #[inline]
+    pub fn fold(&self, _py: Python) -> bool {
+        unsafe { PyDateTime_TIME_GET_FOLD(self.0.as_ptr()) != 0 }
+    }
+
+    /// Gets the time zone, or `None` if the time is naive.
+    #[inline]
+    pub fn tzinfo(&self, py: Python) -> Option<PyTzInfo> {
+        unsafe { tzinfo_from_borrowed_ptr(py, PyDateTime_TIME_GET_TZINFO(self.0.as_ptr())) }
+    }
+}
+
+impl PyDelta {
+    /// Creates a new `datetime.timedelta`.
+    ///
+    /// If `normalize` is true, `seconds` and `microseconds` may be out of range
+    /// and are carried over into the larger units.
+    /// Fails with `OverflowError` if the result exceeds `timedelta.max`.
+    pub fn new(py: Python, days: i32, seconds: i32, microseconds: i32, normalize: bool) -> PyResult<PyDelta> {
+        let api = datetime_api(py);
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Delta_FromDelta)(days as c_int, seconds as c_int, microseconds as c_int,
+                                      normalize as c_int, api.DeltaType))
+        }
+    }
+
+    /// Gets the number of days, in the range `-999999999..=999999999`.
+    #[inline]
+    pub fn days(&self, _py: Python) -> i32 {
+        unsafe { PyDateTime_DELTA_GET_DAYS(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the number of seconds, in the range `0..86400`.
+    #[inline]
+    pub fn seconds(&self, _py: Python) -> i32 {
+        unsafe { PyDateTime_DELTA_GET_SECONDS(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the number of microseconds, in the range `0..1000000`.
+    #[inline]
+    pub fn microseconds(&self, _py: Python) -> i32 {
+        unsafe { PyDateTime_DELTA_GET_MICROSECONDS(self.0.as_ptr()) as i32 }
+    }
+}
+
+impl PyTzInfo {
+    /// Gets the UTC time zone, `datetime.timezone.utc`.
+    pub fn utc(py: Python) -> PyTzInfo {
+        utc(py)
+    }
+}
+
+#[cfg(Py_3_7)]
+fn utc(py: Python) -> PyTzInfo {
+    unsafe {
+        PyObject::from_borrowed_ptr(py, datetime_api(py).TimeZone_UTC).unchecked_cast_into()
+    }
+}
+
+#[cfg(not(Py_3_7))]
+fn utc(py: Python) -> PyTzInfo {
+    use objectprotocol::ObjectProtocol;
+    let result = py.import("datetime")
+        .and_then(|m| m.get(py, "timezone"))
+        .and_then(|tz| tz.getattr(py, "utc"))
+        .and_then(|utc| Ok(utc.cast_into::<PyTzInfo>(py)?));
+    match result {
+        Ok(utc) => utc,
+        Err(e) => {
+            e.print(py);
+            panic!("failed to get datetime.timezone.utc");
+        }
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Converts a `Duration` to a `datetime.timedelta`, truncating to microseconds.
+///
+/// Panics if the duration exceeds `timedelta.max` (999999999 days).
+impl ToPyObject for Duration {
+    type ObjectType = PyDelta;
+
+    fn to_py_object(&self, py: Python) -> PyDelta {
+        let days = self.as_secs() / SECONDS_PER_DAY;
+        if days > 999_999_999 {
+            panic!("Duration is too large to be converted to timedelta");
+        }
+        let seconds = self.as_secs() % SECONDS_PER_DAY;
+        let microseconds = self.subsec_nanos() / 1000;
+        PyDelta::new(py, days as i32, seconds as i32, microseconds as i32, false)
+            .expect("timedelta construction failed")
+    }
+}
+
+/// Allows extracting a `Duration` from a `datetime.timedelta`.
+/// Fails with `ValueError` if the timedelta is negative.
+impl <'source> FromPyObject<'source> for Duration {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let delta = obj.cast_as::<PyDelta>(py)?;
+        let days = delta.days(py);
+        if days < 0 {
+            return Err(PyErr::new::<exc::ValueError, _>(py, "negative timedelta cannot be converted to Duration"));
+        }
+        let secs = days as u64 * SECONDS_PER_DAY + delta.seconds(py) as u64;
+        Ok(Duration::new(secs, delta.microseconds(py) as u32 * 1000))
+    }
+}
+
+fn unix_epoch(py: Python) -> PyDateTime {
+    PyDateTime::new(py, 1970, 1, 1, 0, 0, 0, 0, Some(&utc(py))).expect("failed to create the UNIX epoch")
+}
+
+/// Converts a `SystemTime` to an aware `datetime.datetime` in UTC, truncating to microseconds.
+///
+/// Panics if the time is outside the range of `datetime`.
+impl ToPyObject for SystemTime {
+    type ObjectType = PyDateTime;
+
+    fn to_py_object(&self, py: Python) -> PyDateTime {
+        let epoch = unix_epoch(py);
+        let result = match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => unsafe {
+                let delta = since.to_py_object(py);
+                err::result_cast_from_owned_ptr(py, ffi::PyNumber_Add(epoch.as_object().as_ptr(), delta.as_object().as_ptr()))
+            },
+            Err(e) => unsafe {
+                let delta = e.duration().to_py_object(py);
+                err::result_cast_from_owned_ptr(py, ffi::PyNumber_Subtract(epoch.as_object().as_ptr(), delta.as_object().as_ptr()))
+            }
+        };
+        match result {
+            Ok(dt) => dt,
+            Err(e) => {
+                e.print(py);
+                panic!("SystemTime is out of range for datetime");
+            }
+        }
+    }
+}
+
+// Gets the time since the UNIX epoch as seconds (rounded down) and microseconds (`0..1000000`).
+fn unix_timestamp(py: Python, aware: &PyObject) -> PyResult<(i64, u32)> {
+    let delta = unsafe {
+        err::result_cast_from_owned_ptr::<PyDelta>(py,
+            ffi::PyNumber_Subtract(aware.as_ptr(), unix_epoch(py).as_object().as_ptr()))?
+    };
+    let days = delta.days(py) as i64;
+    Ok((days * SECONDS_PER_DAY as i64 + delta.seconds(py) as i64, delta.microseconds(py) as u32))
+}
+
+/// Allows extracting a `SystemTime` from a `datetime.datetime`.
+///
+/// Naive datetimes are interpreted as local time, like `datetime.timestamp()` does.
+impl <'source> FromPyObject<'source> for SystemTime {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        use objectprotocol::ObjectProtocol;
+        let dt = obj.cast_as::<PyDateTime>(py)?;
+        let aware = match dt.tzinfo(py) {
+            Some(tzinfo) => {
+                tzinfo.release_ref(py);
+                obj.clone_ref(py)
+            }
+            None => obj.call_method(py, "astimezone", ::NoArgs, None)?
+        };
+        let timestamp = unix_timestamp(py, &aware);
+        aware.release_ref(py);
+        let (seconds, microseconds) = timestamp?;
+        let time = if seconds >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(seconds as u64, microseconds * 1000))
+        } else if microseconds == 0 {
+            UNIX_EPOCH.checked_sub(Duration::new(-seconds as u64, 0))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::new((-seconds - 1) as u64, (1_000_000 - microseconds) * 1000))
+        };
+        time.ok_or_else(|| PyErr::new::<exc::OverflowError, _>(py, "datetime is out of range for SystemTime"))
+    }
+}
+
+#[cfg(feature="chrono")]
+mod chrono_impls {
+    use chrono::{Datelike, Timelike, TimeZone, NaiveDate, NaiveDateTime, DateTime, Utc};
+    use chrono::Duration as ChronoDuration;
+    use python::{Python, PythonObject, PyDrop};
+    use conversion::{ToPyObject, FromPyObject};
+    use objects::{PyObject, exc};
+    use err::{PyResult, PyErr};
+    use super::{PyDate, PyDateTime, PyDelta, PyTzInfo, SECONDS_PER_DAY, unix_timestamp};
+
+    fn new_datetime(py: Python, dt: &NaiveDateTime, tzinfo: Option<&PyTzInfo>) -> PyDateTime {
+        // A leap second is stored as `nanosecond() >= 1_000_000_000`, which `datetime` cannot represent.
+        let microsecond = ::std::cmp::min(dt.nanosecond(), 999_999_999) / 1000;
+        PyDateTime::new(py, dt.year(), dt.month() as u8, dt.day() as u8,
+                        dt.hour() as u8, dt.minute() as u8, dt.second() as u8, microsecond, tzinfo)
+            .expect("NaiveDateTime is out of range for datetime")
+    }
+
+    fn to_naive_datetime(py: Python, dt: &PyDateTime) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(dt.year(py), dt.month(py) as u32, dt.day(py) as u32)
+            .and_then(|date| date.and_hms_micro_opt(dt.hour(py) as u32, dt.minute(py) as u32,
+                                                    dt.second(py) as u32, dt.microsecond(py)))
+            .expect("datetime is always a valid NaiveDateTime")
+    }
+
+    /// Converts a `NaiveDate` to a `datetime.date`.
+    ///
+    /// Panics if the year is outside the range `1..=9999`.
+    impl ToPyObject for NaiveDate {
+        type ObjectType = PyDate;
+
+        fn to_py_object(&self, py: Python) -> PyDate {
+            PyDate::new(py, self.year(), self.month() as u8, self.day() as u8)
+                .expect("NaiveDate is out of range for date")
+        }
+    }
+
+    /// Allows extracting a `NaiveDate` from a `datetime.date`.
+    impl <'source> FromPyObject<'source> for NaiveDate {
+        fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+            let date = obj.cast_as::<PyDate>(py)?;
+            Ok(NaiveDate::from_ymd_opt(date.year(py), date.month(py) as u32, date.day(py) as u32)
+                .expect("date is always a valid NaiveDate"))
+        }
+    }
+
+    /// Converts a `NaiveDateTime` to a naive `datetime.datetime`, truncating to microseconds.
+    ///
+    /// Panics if the year is outside the range `1..=9999`.
+    impl ToPyObject for NaiveDateTime {
+        type ObjectType = PyDateTime;
+
+        fn to_py_object(&self, py: Python) -> PyDateTime {
+            new_datetime(py, self, None)
+        }
+    }
+
+    /// Allows extracting a `NaiveDateTime` from a naive `datetime.datetime`.
+    /// Fails with `TypeError` if the datetime has a time zone.
+    impl <'source> FromPyObject<'source> for NaiveDateTime {
+        fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+            let dt = obj.cast_as::<PyDateTime>(py)?;
+            if let Some(tzinfo) = dt.tzinfo(py) {
+                tzinfo.release_ref(py);
+                return Err(PyErr::new::<exc::TypeError, _>(py, "expected a naive datetime"));
+            }
+            Ok(to_naive_datetime(py, dt))
+        }
+    }
+
+    /// Converts a `DateTime<Utc>` to an aware `datetime.datetime` in UTC, truncating to microseconds.
+    ///
+    /// Panics if the year is outside the range `1..=9999`.
+    impl ToPyObject for DateTime<Utc> {
+        type ObjectType = PyDateTime;
+
+        fn to_py_object(&self, py: Python) -> PyDateTime {
+            new_datetime(py, &self.naive_utc(), Some(&PyTzInfo::utc(py)))
+        }
+    }
+
+    /// Allows extracting a `DateTime<Utc>` from an aware `datetime.datetime` in any time zone.
+    /// Fails with `TypeError` if the datetime is naive.
+    impl <'source> FromPyObject<'source> for DateTime<Utc> {
+        fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+            let dt = obj.cast_as::<PyDateTime>(py)?;
+            match dt.tzinfo(py) {
+                Some(tzinfo) => tzinfo.release_ref(py),
+                None => return Err(PyErr::new::<exc::TypeError, _>(py, "expected a datetime with a time zone"))
+            }
+            let (seconds, microseconds) = unix_timestamp(py, obj)?;
+            Ok(Utc.timestamp_opt(seconds, microseconds * 1000).single()
+                .expect("datetime is always a valid DateTime<Utc>"))
+        }
+    }
+
+    /// Converts a `chrono::Duration` to a `datetime.timedelta`, truncating to microseconds.
+    ///
+    /// Panics if the duration exceeds the range of `timedelta` (999999999 days).
+    impl ToPyObject for ChronoDuration {
+        type ObjectType = PyDelta;
+
+        fn to_py_object(&self, py: Python) -> PyDelta {
+            let seconds = self.num_seconds();
+            let days = seconds / SECONDS_PER_DAY as i64;
+            if days.abs() > 999_999_999 {
+                panic!("Duration is too large to be converted to timedelta");
+            }
+            // `seconds` and `microseconds` have the same sign as `self`; `normalize` fixes that up.
+            PyDelta::new(py, days as i32, (seconds % SECONDS_PER_DAY as i64) as i32,
+                         self.subsec_nanos() / 1000, true)
+                .expect("Duration is out of range for timedelta")
+        }
+    }
+
+    /// Allows extracting a `chrono::Duration` from a `datetime.timedelta`.
+    impl <'source> FromPyObject<'source> for ChronoDuration {
+        fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+            let delta = obj.cast_as::<PyDelta>(py)?;
+            let seconds = delta.days(py) as i64 * SECONDS_PER_DAY as i64 + delta.seconds(py) as i64;
+            Ok(ChronoDuration::seconds(seconds) + ChronoDuration::microseconds(delta.microseconds(py) as i64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use python::{Python, PythonObject, PythonObjectWithTypeObject};
+    use conversion::ToPyObject;
+    use objects::PyDict;
+    use super::{PyDate, PyDateTime, PyTime, PyDelta, PyTzInfo};
+
+    #[test]
+    fn test_date() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let date = PyDate::new(py, 2018, 2, 28).unwrap();
+        assert_eq!((date.year(py), date.month(py), date.day(py)), (2018, 2, 28));
+        assert!(PyDate::new(py, 2018, 2, 29).is_err());
+        let d = PyDict::new(py);
+        d.set_item(py, "d", &date).unwrap();
+        py.run("import datetime; assert d == datetime.date(2018, 2, 28)", None, Some(&d)).unwrap();
+        assert_eq!(PyDate::type_object(py).name(py), "datetime.date");
+    }
+
+    #[test]
+    fn test_datetime() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let utc = PyTzInfo::utc(py);
+        let dt = PyDateTime::new(py, 2020, 12, 31, 23, 59, 58, 123456, Some(&utc)).unwrap();
+        assert_eq!((dt.year(py), dt.month(py), dt.day(py)), (2020, 12, 31));
+        assert_eq!((dt.hour(py), dt.minute(py), dt.second(py), dt.microsecond(py)), (23, 59, 58, 123456));
+        assert!(dt.tzinfo(py).unwrap().as_object() == utc.as_object());
+        assert!(dt.as_object().cast_as::<PyDate>(py).is_ok());
+        assert!(dt.as_object().cast_as::<PyTime>(py).is_err());
+
+        let naive = py.eval("__import__('datetime').datetime(1999, 1, 2, 3, 4, 5)", None, None).unwrap();
+        let naive = naive.cast_into::<PyDateTime>(py).unwrap();
+        assert_eq!(naive.hour(py), 3);
+        assert!(naive.tzinfo(py).is_none());
+
+        let dt = PyDateTime::from_timestamp(py, 86400.5, Some(&utc)).unwrap();
+        assert_eq!((dt.day(py), dt.microsecond(py)), (2, 500000));
+    }
+
+    #[test]
+    fn test_time_and_delta() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let time = PyTime::new(py, 12, 30, 15, 7, None).unwrap();
+        assert_eq!((time.hour(py), time.minute(py), time.second(py), time.microsecond(py)), (12, 30, 15, 7));
+        assert!(time.tzinfo(py).is_none());
+        assert!(PyTime::new(py, 24, 0, 0, 0, None).is_err());
+
+        let delta = PyDelta::new(py, 0, -1, 0, true).unwrap();
+        assert_eq!((delta.days(py), delta.seconds(py), delta.microseconds(py)), (-1, 86399, 0));
+    }
+
+    #[test]
+    fn test_duration() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let duration = Duration::new(3 * 86400 + 5, 123_456_789);
+        let delta = duration.to_py_object(py);
+        assert_eq!((delta.days(py), delta.seconds(py), delta.microseconds(py)), (3, 5, 123456));
+        assert_eq!(delta.into_object().extract::<Duration>(py).unwrap(), Duration::new(3 * 86400 + 5, 123_456_000));
+        let negative = PyDelta::new(py, -1, 0, 0, false).unwrap();
+        assert!(negative.into_object().extract::<Duration>(py).is_err());
+    }
+
+    #[test]
+    fn test_system_time() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for &time in &[UNIX_EPOCH + Duration::new(1_500_000_000, 250_000_000),
+                       UNIX_EPOCH - Duration::new(86400 * 365, 1000)] {
+            let dt = time.to_py_object(py);
+            assert_eq!(dt.as_object().extract::<SystemTime>(py).unwrap(), time);
+        }
+        let dt = (UNIX_EPOCH + Duration::from_secs(1_500_000_000)).to_py_object(py);
+        assert_eq!((dt.year(py), dt.month(py), dt.day(py), dt.hour(py)), (2017, 7, 14, 2));
+        let d = PyDict::new(py);
+        d.set_item(py, "dt", &dt).unwrap();
+        py.run("assert dt.timestamp() == 1500000000", None, Some(&d)).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature="chrono")]
+    fn test_chrono() {
+        use chrono::{NaiveDate, NaiveDateTime, DateTime, Utc, TimeZone};
+        use chrono::Duration as ChronoDuration;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        let date = NaiveDate::from_ymd_opt(2018, 2, 28).unwrap();
+        let obj = date.to_py_object(py).into_object();
+        assert_eq!(obj.extract::<NaiveDate>(py).unwrap(), date);
+
+        let naive = date.and_hms_micro_opt(13, 14, 15, 123456).unwrap();
+        let dt = naive.to_py_object(py);
+        assert_eq!((dt.hour(py), dt.microsecond(py)), (13, 123456));
+        assert!(dt.tzinfo(py).is_none());
+        assert_eq!(dt.as_object().extract::<NaiveDateTime>(py).unwrap(), naive);
+        assert!(dt.as_object().extract::<DateTime<Utc>>(py).is_err());
+
+        let utc = Utc.from_utc_datetime(&naive);
+        let dt = utc.to_py_object(py);
+        assert!(dt.tzinfo(py).unwrap().as_object() == PyTzInfo::utc(py).as_object());
+        assert_eq!(dt.as_object().extract::<DateTime<Utc>>(py).unwrap(), utc);
+        assert!(dt.as_object().extract::<NaiveDateTime>(py).is_err());
+        let dt = py.eval("__import__('datetime').datetime(1969, 12, 31, 23, 0, 0, 500000, \
+                          __import__('datetime').timezone(__import__('datetime').timedelta(hours=-2)))",
+                         None, None).unwrap();
+        assert_eq!(dt.extract::<DateTime<Utc>>(py).unwrap(),
+                   Utc.timestamp_opt(3600, 500_000_000).single().unwrap());
+
+        for &duration in &[ChronoDuration::microseconds(-1), ChronoDuration::seconds(3 * 86400 + 5),
+                           ChronoDuration::seconds(-86400 - 1) + ChronoDuration::microseconds(-250)] {
+            let delta = duration.to_py_object(py);
+            assert_eq!(delta.as_object().extract::<ChronoDuration>(py).unwrap(), duration);
+        }
+        let delta = ChronoDuration::microseconds(-1).to_py_object(py);
+        assert_eq!((delta.days(py), delta.seconds(py), delta.microseconds(py)), (-1, 86399, 999999));
+    }
+}
@@ -16,12 +16,14 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use libc::c_int;
 use ffi;
-use python::{Python, PythonObject};
-use conversion::ToPyObject;
-use objects::{PyObject, PyList};
+use python::{Python, PythonObject, PyDrop};
+use conversion::{ToPyObject, FromPyObject};
+use objectprotocol::ObjectProtocol;
+use objects::{PyObject, PyList, exc};
 use err::{self, PyResult, PyErr};
-use std::{mem, collections, hash, cmp};
+use std::{mem, ptr, collections, hash, cmp};
 
 /// Represents a Python `dict`.
 pub struct PyDict(PyObject);
@@ -73,6 +75,7 @@ impl PyDict {
 
     /// Gets an item from the dictionary.
     /// Returns None if the item is not present, or if an error occurs.
+    /// Use `try_get_item()` to distinguish the two cases.
     pub fn get_item<K>(&self, py: Python, key: K) -> Option<PyObject> where K: ToPyObject {
         key.with_borrowed_ptr(py, |key| unsafe {
             PyObject::from_borrowed_ptr_opt(py,
@@ -80,6 +83,21 @@ impl PyDict {
         })
     }
 
+    /// Gets an item from the dictionary.
+    ///
+    /// Returns `Ok(None)` if the item is not present, and `Err` if the lookup
+    /// fails (e.g. because the key is unhashable).
+    pub fn try_get_item<K>(&self, py: Python, key: K) -> PyResult<Option<PyObject>> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            let item = get_item_with_error(self.0.as_ptr(), key);
+            if item.is_null() && !ffi::PyErr_Occurred().is_null() {
+                Err(PyErr::fetch(py))
+            } else {
+                Ok(PyObject::from_borrowed_ptr_opt(py, item))
+            }
+        })
+    }
+
     /// Sets an item value.
     /// This is equivalent to the Python expression `self[key] = value`.
     pub fn set_item<K, V>(&self, py: Python, key: K, value: V) -> PyResult<()> where K: ToPyObject, V: ToPyObject {
@@ -99,6 +117,92 @@ impl PyDict {
         })
     }
 
+    /// Returns the value of `key`; if the key is not present, inserts `default` and returns it.
+    /// This is equivalent to the Python expression `self.setdefault(key, default)`.
+    pub fn setdefault<K, V>(&self, py: Python, key: K, default: V) -> PyResult<PyObject> where K: ToPyObject, V: ToPyObject {
+        key.with_borrowed_ptr(py, |key| {
+            let key = unsafe { PyObject::from_borrowed_ptr(py, key) };
+            if let Some(value) = self.try_get_item(py, &key)? {
+                return Ok(value);
+            }
+            let default = default.into_py_object(py).into_object();
+            self.set_item(py, key, &default)?;
+            Ok(default)
+        })
+    }
+
+    /// Removes `key` from the dictionary and returns its value,
+    /// or returns `None` if the key is not present.
+    /// This is equivalent to the Python expression `self.pop(key, None)`.
+    pub fn pop<K>(&self, py: Python, key: K) -> PyResult<Option<PyObject>> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| {
+            let key = unsafe { PyObject::from_borrowed_ptr(py, key) };
+            match self.try_get_item(py, &key)? {
+                Some(value) => {
+                    self.del_item(py, key)?;
+                    Ok(Some(value))
+                }
+                None => Ok(None)
+            }
+        })
+    }
+
+    /// Updates the dictionary with the key-value pairs of a mapping, overwriting existing keys.
+    /// This is equivalent to the Python expression `self.update(other)` for mappings.
+    pub fn update(&self, py: Python, other: &PyObject) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py, ffi::PyDict_Update(self.0.as_ptr(), other.as_ptr()))
+        }
+    }
+
+    /// Adds the key-value pairs of a mapping to the dictionary.
+    /// Existing keys are only overwritten if `override_existing` is true.
+    pub fn merge(&self, py: Python, other: &PyObject, override_existing: bool) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyDict_Merge(self.0.as_ptr(), other.as_ptr(), override_existing as c_int))
+        }
+    }
+
+    /// Adds the key-value pairs from an iterable of pairs (e.g. a list of 2-tuples) to the dictionary.
+    /// Existing keys are only overwritten if `override_existing` is true.
+    ///
+    /// With `override_existing == true`, this is equivalent to `self.update(pairs)` for sequences of pairs.
+    pub fn merge_from_pairs(&self, py: Python, pairs: &PyObject, override_existing: bool) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyDict_MergeFromSeq2(self.0.as_ptr(), pairs.as_ptr(), override_existing as c_int))
+        }
+    }
+
+    /// Creates a new dictionary from an iterator of key-value pairs.
+    /// Later pairs overwrite earlier pairs with the same key.
+    pub fn from_pairs<I, K, V>(py: Python, pairs: I) -> PyResult<PyDict>
+        where I: IntoIterator<Item=(K, V)>, K: ToPyObject, V: ToPyObject
+    {
+        let dict = PyDict::new(py);
+        for (key, value) in pairs {
+            dict.set_item(py, key, value)?;
+        }
+        Ok(dict)
+    }
+
+    /// List of dict keys.
+    /// This is equivalent to the python expression `list(dict.keys())`.
+    pub fn keys_list(&self, py: Python) -> PyList {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PyDict_Keys(self.0.as_ptr()))
+        }
+    }
+
+    /// List of dict values.
+    /// This is equivalent to the python expression `list(dict.values())`.
+    pub fn values_list(&self, py: Python) -> PyList {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PyDict_Values(self.0.as_ptr()))
+        }
+    }
+
     // List of dict items.
     // This is equivalent to the python expression `list(dict.items())`.
     pub fn items_list(&self, py: Python) -> PyList {
@@ -108,10 +212,9 @@ impl PyDict {
     }
 
     /// Returns the list of (key,value) pairs in this dictionary.
+    ///
+    /// To iterate over the dictionary without copying the pairs into a `Vec`, use `iter()`.
     pub fn items(&self, py: Python) -> Vec<(PyObject, PyObject)> {
-        // Note that we don't provide an iterator because
-        // PyDict_Next() is unsafe to use when the dictionary might be changed
-        // by other python code.
         let mut vec = Vec::with_capacity(self.len(py));
         unsafe {
             let mut pos = 0;
@@ -124,6 +227,115 @@ impl PyDict {
         }
         vec
     }
+
+    /// Returns an iterator over the (key, value) pairs in this dictionary.
+    ///
+    /// If the size of the dictionary changes during iteration, the iterator
+    /// yields a `RuntimeError` and then stops, like iterating over a dict in Python.
+    #[inline]
+    pub fn iter<'a, 'p>(&'a self, py: Python<'p>) -> PyDictItems<'a, 'p> {
+        PyDictItems { py: py, dict: self, pos: 0, len: self.len(py), done: false }
+    }
+
+    /// Returns an iterator over the keys in this dictionary.
+    /// See `iter()` for details.
+    #[inline]
+    pub fn keys<'a, 'p>(&'a self, py: Python<'p>) -> PyDictKeys<'a, 'p> {
+        PyDictKeys(self.iter(py))
+    }
+
+    /// Returns an iterator over the values in this dictionary.
+    /// See `iter()` for details.
+    #[inline]
+    pub fn values<'a, 'p>(&'a self, py: Python<'p>) -> PyDictValues<'a, 'p> {
+        PyDictValues(self.iter(py))
+    }
+}
+
+// Like `PyDict_GetItem`, but leaves the exception set if the lookup fails.
+#[cfg(feature="python3-sys")]
+unsafe fn get_item_with_error(dict: *mut ffi::PyObject, key: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    ffi::PyDict_GetItemWithError(dict, key)
+}
+
+// Python 2 has no `PyDict_GetItemWithError`, and `PyDict_GetItem` swallows errors
+// from hashing or comparing the key. `PyDict_Contains` reports them, so use it
+// to check for the key first.
+// (An error in the comparisons of the second lookup is still lost.)
+#[cfg(feature="python27-sys")]
+unsafe fn get_item_with_error(dict: *mut ffi::PyObject, key: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    match ffi::PyDict_Contains(dict, key) {
+        1 => ffi::PyDict_GetItem(dict, key),
+        _ => ptr::null_mut()
+    }
+}
+
+/// Used by `PyDict::iter()`.
+pub struct PyDictItems<'a, 'p> {
+    py: Python<'p>,
+    dict: &'a PyDict,
+    pos: ffi::Py_ssize_t,
+    len: usize,
+    done: bool
+}
+
+impl <'a, 'p> PyDictItems<'a, 'p> {
+    // Returns borrowed references to the next key and value.
+    fn next_ptrs(&mut self) -> Option<PyResult<(*mut ffi::PyObject, *mut ffi::PyObject)>> {
+        if self.done {
+            return None;
+        }
+        if self.dict.len(self.py) != self.len {
+            self.done = true;
+            return Some(Err(PyErr::new::<exc::RuntimeError, _>(self.py, "dictionary changed size during iteration")));
+        }
+        let mut key: *mut ffi::PyObject = ptr::null_mut();
+        let mut value: *mut ffi::PyObject = ptr::null_mut();
+        if unsafe { ffi::PyDict_Next(self.dict.as_object().as_ptr(), &mut self.pos, &mut key, &mut value) } != 0 {
+            Some(Ok((key, value)))
+        } else {
+            self.done = true;
+            None
+        }
+    }
+}
+
+impl <'a, 'p> Iterator for PyDictItems<'a, 'p> {
+    type Item = PyResult<(PyObject, PyObject)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let py = self.py;
+        self.next_ptrs().map(|r| r.map(|(key, value)| unsafe {
+            (PyObject::from_borrowed_ptr(py, key), PyObject::from_borrowed_ptr(py, value))
+        }))
+    }
+}
+
+/// Used by `PyDict::keys()`.
+pub struct PyDictKeys<'a, 'p>(PyDictItems<'a, 'p>);
+
+impl <'a, 'p> Iterator for PyDictKeys<'a, 'p> {
+    type Item = PyResult<PyObject>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let py = self.0.py;
+        self.0.next_ptrs().map(|r| r.map(|(key, _)| unsafe { PyObject::from_borrowed_ptr(py, key) }))
+    }
+}
+
+/// Used by `PyDict::values()`.
+pub struct PyDictValues<'a, 'p>(PyDictItems<'a, 'p>);
+
+impl <'a, 'p> Iterator for PyDictValues<'a, 'p> {
+    type Item = PyResult<PyObject>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let py = self.0.py;
+        self.0.next_ptrs().map(|r| r.map(|(_, value)| unsafe { PyObject::from_borrowed_ptr(py, value) }))
+    }
 }
 
 impl <K, V, H> ToPyObject for collections::HashMap<K, V, H>
@@ -157,12 +369,69 @@ impl <K, V> ToPyObject for collections::BTreeMap<K, V>
     }
 }
 
+/// Calls `f` for each key-value pair of a mapping.
+///
+/// Dicts are iterated directly; other mappings are iterated using their `items()` method.
+fn for_each_item<F>(py: Python, obj: &PyObject, mut f: F) -> PyResult<()>
+    where F: FnMut(&PyObject, &PyObject) -> PyResult<()>
+{
+    if let Ok(dict) = obj.cast_as::<PyDict>(py) {
+        for item in dict.iter(py) {
+            let (key, value) = item?;
+            f(&key, &value)?;
+            key.release_ref(py);
+            value.release_ref(py);
+        }
+        return Ok(());
+    }
+    let items = obj.call_method(py, "items", ::NoArgs, None)?;
+    for item in items.iter(py)? {
+        let item = item?;
+        let (key, value): (PyObject, PyObject) = item.extract(py)?;
+        f(&key, &value)?;
+        item.release_ref(py);
+    }
+    Ok(())
+}
+
+/// Extracts a `HashMap` from a `dict` or any other mapping with an `items()` method.
+impl <'source, K, V, H> FromPyObject<'source> for collections::HashMap<K, V, H>
+    where for<'a> K: FromPyObject<'a> + hash::Hash + cmp::Eq,
+          for<'a> V: FromPyObject<'a>,
+          H: hash::BuildHasher + Default
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut map = collections::HashMap::default();
+        for_each_item(py, obj, |key, value| {
+            map.insert(key.extract(py)?, value.extract(py)?);
+            Ok(())
+        })?;
+        Ok(map)
+    }
+}
+
+/// Extracts a `BTreeMap` from a `dict` or any other mapping with an `items()` method.
+impl <'source, K, V> FromPyObject<'source> for collections::BTreeMap<K, V>
+    where for<'a> K: FromPyObject<'a> + cmp::Ord,
+          for<'a> V: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut map = collections::BTreeMap::new();
+        for_each_item(py, obj, |key, value| {
+            map.insert(key.extract(py)?, value.extract(py)?);
+            Ok(())
+        })?;
+        Ok(map)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
     use objects::{PyDict, PyTuple};
-    use std::collections::HashMap;
+    use objects::tests::{user_dict, extract_sorted};
+    use std::collections::{HashMap, BTreeMap};
 
     #[test]
     fn test_len() {
@@ -289,4 +558,125 @@ mod test {
         assert_eq!(32 + 42 + 123, value_sum);
     }
 
+    #[test]
+    fn test_extract_hashmap() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("{'a': 1, 'b': 2}", None, None).unwrap();
+        let map = obj.extract::<HashMap<String, i64>>(py).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+        assert!(obj.extract::<HashMap<String, String>>(py).is_err());
+        assert!(py.eval("[1, 2]", None, None).unwrap().extract::<HashMap<i32, i32>>(py).is_err());
+    }
+
+    #[test]
+    fn test_extract_btreemap_from_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("__import__('collections').OrderedDict([(3, 'c'), (1, 'a')])", None, None).unwrap();
+        let map = obj.extract::<BTreeMap<i32, String>>(py).unwrap();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![(1, "a".to_string()), (3, "c".to_string())]);
+        let obj = user_dict(py, &PyDict::from_pairs(py, vec![("x", 1.5)]).unwrap());
+        let map = obj.extract::<BTreeMap<String, f64>>(py).unwrap();
+        assert_eq!(map["x"], 1.5);
+    }
+
+
+    #[test]
+    fn test_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        v.insert(8, 42);
+        v.insert(9, 123);
+        let dict = v.to_py_object(py);
+        let mut key_sum = 0;
+        let mut value_sum = 0;
+        for item in dict.iter(py) {
+            let (key, value) = item.unwrap();
+            key_sum += key.extract::<i32>(py).unwrap();
+            value_sum += value.extract::<i32>(py).unwrap();
+        }
+        assert_eq!(7 + 8 + 9, key_sum);
+        assert_eq!(32 + 42 + 123, value_sum);
+        assert_eq!(dict.keys(py).map(|k| k.unwrap().extract::<i32>(py).unwrap()).sum::<i32>(), 24);
+        assert_eq!(dict.values(py).map(|v| v.unwrap().extract::<i32>(py).unwrap()).sum::<i32>(), 197);
+    }
+
+    #[test]
+    fn test_iter_size_changed() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, 1, 1).unwrap();
+        dict.set_item(py, 2, 2).unwrap();
+        let mut iter = dict.keys(py);
+        assert!(iter.next().unwrap().is_ok());
+        dict.set_item(py, 3, 3).unwrap();
+        let err = iter.next().unwrap().unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::RuntimeError>()));
+        assert!(iter.next().is_none());
+
+        // replacing values doesn't change the size
+        let mut count = 0;
+        for item in dict.iter(py) {
+            let (key, _) = item.unwrap();
+            dict.set_item(py, key, 0).unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+
+    #[test]
+    fn test_try_get_item() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::from_pairs(py, vec![(7, 32)]).unwrap();
+        assert_eq!(32, dict.try_get_item(py, 7).unwrap().unwrap().extract::<i32>(py).unwrap());
+        assert!(dict.try_get_item(py, 8).unwrap().is_none());
+        let unhashable = PyDict::new(py);
+        let err = dict.try_get_item(py, &unhashable).unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::TypeError>()));
+        assert!(dict.get_item(py, &unhashable).is_none());
+        // a key that collides with `7`, but fails to compare
+        let bad_eq = py.eval("type('BadEq', (object,), { '__hash__': lambda self: 7, '__eq__': lambda self, other: 1 / 0 })()",
+            None, None).unwrap();
+        let err = dict.try_get_item(py, &bad_eq).unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::ZeroDivisionError>()));
+    }
+
+    #[test]
+    fn test_setdefault_and_pop() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        assert_eq!(dict.setdefault(py, "a", 1).unwrap().extract::<i32>(py).unwrap(), 1);
+        assert_eq!(dict.setdefault(py, "a", 2).unwrap().extract::<i32>(py).unwrap(), 1);
+        assert_eq!(dict.pop(py, "a").unwrap().unwrap().extract::<i32>(py).unwrap(), 1);
+        assert!(dict.pop(py, "a").unwrap().is_none());
+        assert_eq!(dict.len(py), 0);
+    }
+
+    #[test]
+    fn test_update_and_merge() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::from_pairs(py, vec![("a", 1), ("b", 2)]).unwrap();
+        let other = PyDict::from_pairs(py, vec![("b", 20), ("c", 30)]).unwrap();
+        dict.merge(py, other.as_object(), false).unwrap();
+        assert_eq!(dict.get_item(py, "b").unwrap().extract::<i32>(py).unwrap(), 2);
+        dict.update(py, other.as_object()).unwrap();
+        assert_eq!(dict.get_item(py, "b").unwrap().extract::<i32>(py).unwrap(), 20);
+        let pairs = vec![("c", 300), ("d", 400)].to_py_object(py);
+        dict.merge_from_pairs(py, pairs.as_object(), true).unwrap();
+        assert_eq!(dict.get_item(py, "c").unwrap().extract::<i32>(py).unwrap(), 300);
+        assert!(dict.update(py, &py.None()).is_err());
+
+        assert_eq!(extract_sorted::<String>(py, dict.keys_list(py).as_object()), ["a", "b", "c", "d"]);
+        assert_eq!(extract_sorted::<i32>(py, dict.values_list(py).as_object()), [1, 20, 300, 400]);
+    }
 }
@@ -16,9 +16,12 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use std::{cmp, collections, ptr};
 use python::{Python, PythonObject, ToPythonPointer, PyClone, PyDrop};
 use err::{self, PyErr, PyResult};
+use objectprotocol::ObjectProtocol;
 use super::object::PyObject;
+use super::{PyTuple, exc};
 use ffi::{self, Py_ssize_t};
 use conversion::{ToPyObject, FromPyObject};
 
@@ -49,9 +52,17 @@ impl PyList {
         }
     }
 
+    /// Construct a new list from an iterator with a known length,
+    /// without collecting the elements into an intermediate `Vec`.
+    pub fn from_iter<I>(py: Python, elements: I) -> PyList
+        where I: IntoIterator, I::IntoIter: ExactSizeIterator, I::Item: ToPyObject
+    {
+        list_from_iter(py, elements.into_iter())
+    }
+
     /// Gets the item at the specified index.
     ///
-    /// Panics if the index is out of range.
+    /// Panics if the index is out of range; use `try_get_item()` to get an `IndexError` instead.
     pub fn get_item(&self, py: Python, index: usize) -> PyObject {
         // TODO: do we really want to panic here?
         assert!(index < self.len(py));
@@ -60,9 +71,20 @@ impl PyList {
         }
     }
 
+    /// Gets the item at the specified index.
+    ///
+    /// Fails with `IndexError` if the index is out of range.
+    pub fn try_get_item(&self, py: Python, index: usize) -> PyResult<PyObject> {
+        if index < self.len(py) {
+            Ok(self.get_item(py, index))
+        } else {
+            Err(PyErr::new::<exc::IndexError, _>(py, "list index out of range"))
+        }
+    }
+
     /// Sets the item at the specified index.
     ///
-    /// Panics if the index is out of range.
+    /// Panics if the index is out of range; use `try_set_item()` to get an `IndexError` instead.
     pub fn set_item(&self, _py: Python, index: usize, item: PyObject) {
         let r = unsafe { ffi::PyList_SetItem(self.0.as_ptr(), index as Py_ssize_t, item.steal_ptr()) };
         assert!(r == 0);
@@ -76,6 +98,125 @@ impl PyList {
         assert!(r == 0);
     }
 
+    /// Sets the item at the specified index.
+    ///
+    /// Fails with `IndexError` if the index is out of range.
+    pub fn try_set_item(&self, py: Python, index: usize, item: PyObject) -> PyResult<()> {
+        if index > Py_ssize_t::max_value() as usize {
+            return Err(PyErr::new::<exc::IndexError, _>(py, "list assignment index out of range"));
+        }
+        unsafe {
+            err::error_on_minusone(py, ffi::PyList_SetItem(self.0.as_ptr(), index as Py_ssize_t, item.steal_ptr()))
+        }
+    }
+
+    /// Appends an item to the end of the list.
+    /// This is equivalent to the Python expression `self.append(item)`.
+    pub fn append<I>(&self, py: Python, item: I) -> PyResult<()> where I: ToPyObject {
+        item.with_borrowed_ptr(py, |item| unsafe {
+            err::error_on_minusone(py, ffi::PyList_Append(self.0.as_ptr(), item))
+        })
+    }
+
+    /// Appends all items of an iterable to the end of the list.
+    /// This is equivalent to the Python expression `self.extend(iterable)`.
+    pub fn extend(&self, py: Python, iterable: &PyObject) -> PyResult<()> {
+        let len = self.len(py);
+        self.set_slice(py, len, len, iterable)
+    }
+
+    /// Removes and returns the last item of the list.
+    ///
+    /// Fails with `IndexError` if the list is empty.
+    pub fn pop(&self, py: Python) -> PyResult<PyObject> {
+        self.0.call_method(py, "pop", ::NoArgs, None)
+    }
+
+    /// Removes the first item that is equal to `value`.
+    ///
+    /// Fails with `ValueError` if there is no such item.
+    pub fn remove<V>(&self, py: Python, value: V) -> PyResult<()> where V: ToPyObject {
+        self.0.call_method(py, "remove", (value,), None)?.release_ref(py);
+        Ok(())
+    }
+
+    /// Sorts the list in place.
+    /// This is equivalent to the Python expression `self.sort()`.
+    pub fn sort(&self, py: Python) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py, ffi::PyList_Sort(self.0.as_ptr()))
+        }
+    }
+
+    /// Sorts the list in place, comparing the keys returned by `key`.
+    ///
+    /// The sort is stable, and `key` is called exactly once per item.
+    /// This is equivalent to the Python expression `self.sort(key=key)`.
+    pub fn sort_by_key<F, K>(&self, py: Python, mut key: F) -> PyResult<()>
+        where F: FnMut(Python, &PyObject) -> PyResult<K>, K: ToPyObject
+    {
+        let mut decorated = Vec::with_capacity(self.len(py));
+        for (index, item) in self.iter(py).enumerate() {
+            let key = key(py, &item)?;
+            decorated.push((key, index, item).into_py_object(py).into_object());
+        }
+        // The indices are unique, so the items themselves are never compared.
+        let decorated = PyList::new(py, &decorated);
+        decorated.sort(py)?;
+        if decorated.len(py) != self.len(py) {
+            return Err(PyErr::new::<exc::ValueError, _>(py, "list modified during sort"));
+        }
+        for (index, entry) in decorated.iter(py).enumerate() {
+            let (_, _, item): (PyObject, usize, PyObject) = entry.extract(py)?;
+            self.try_set_item(py, index, item)?;
+        }
+        Ok(())
+    }
+
+    /// Reverses the list in place.
+    /// This is equivalent to the Python expression `self.reverse()`.
+    pub fn reverse(&self, py: Python) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py, ffi::PyList_Reverse(self.0.as_ptr()))
+        }
+    }
+
+    /// Returns a new list with the items from `low` to `high`.
+    /// This is equivalent to the Python expression `self[low:high]`;
+    /// out-of-range indices are clamped to the length of the list.
+    pub fn get_slice(&self, py: Python, low: usize, high: usize) -> PyList {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py,
+                ffi::PyList_GetSlice(self.0.as_ptr(), clamp_index(low), clamp_index(high)))
+        }
+    }
+
+    /// Replaces the items from `low` to `high` with the items of an iterable.
+    /// This is equivalent to the Python expression `self[low:high] = items`.
+    pub fn set_slice(&self, py: Python, low: usize, high: usize, items: &PyObject) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyList_SetSlice(self.0.as_ptr(), clamp_index(low), clamp_index(high), items.as_ptr()))
+        }
+    }
+
+    /// Deletes the items from `low` to `high`.
+    /// This is equivalent to the Python expression `del self[low:high]`.
+    pub fn del_slice(&self, py: Python, low: usize, high: usize) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyList_SetSlice(self.0.as_ptr(), clamp_index(low), clamp_index(high), ptr::null_mut()))
+        }
+    }
+
+    /// Returns a new tuple with the items of the list.
+    /// This is equivalent to the Python expression `tuple(self)`.
+    pub fn as_tuple(&self, py: Python) -> PyTuple {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PyList_AsTuple(self.0.as_ptr()))
+        }
+    }
+
     #[inline]
     pub fn iter<'a, 'p>(&'a self, py: Python<'p>) -> PyListIterator<'a, 'p> {
         PyListIterator { py: py, list: self, index: 0 }
@@ -131,15 +272,76 @@ impl <T> ToPyObject for Vec<T> where T: ToPyObject {
     }
 
     fn into_py_object(self, py: Python) -> PyList {
-        unsafe {
-            let ptr = ffi::PyList_New(self.len() as Py_ssize_t);
-            let t = err::cast_from_owned_ptr_or_panic(py, ptr);
-            for (i, e) in self.into_iter().enumerate() {
-                let obj = e.into_py_object(py).into_object();
-                ffi::PyList_SetItem(ptr, i as Py_ssize_t, obj.steal_ptr());
+        list_from_iter(py, self.into_iter())
+    }
+}
+
+impl <T> ToPyObject for collections::VecDeque<T> where T: ToPyObject {
+    type ObjectType = PyList;
+
+    fn to_py_object(&self, py: Python) -> PyList {
+        list_from_iter(py, self.iter())
+    }
+
+    fn into_py_object(self, py: Python) -> PyList {
+        list_from_iter(py, self.into_iter())
+    }
+}
+
+impl <T> ToPyObject for collections::LinkedList<T> where T: ToPyObject {
+    type ObjectType = PyList;
+
+    fn to_py_object(&self, py: Python) -> PyList {
+        list_from_iter(py, self.iter())
+    }
+
+    fn into_py_object(self, py: Python) -> PyList {
+        list_from_iter(py, self.into_iter())
+    }
+}
+
+macro_rules! array_to_py_object {
+    ($($N:expr)+) => {
+        $(
+            impl <T> ToPyObject for [T; $N] where T: ToPyObject {
+                type ObjectType = PyList;
+
+                #[inline]
+                fn to_py_object(&self, py: Python) -> PyList {
+                    self[..].to_py_object(py)
+                }
             }
-            t
+        )+
+    }
+}
+
+array_to_py_object!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+
+fn clamp_index(index: usize) -> Py_ssize_t {
+    cmp::min(index, Py_ssize_t::max_value() as usize) as Py_ssize_t
+}
+
+fn list_from_iter<I>(py: Python, iter: I) -> PyList
+    where I: ExactSizeIterator, I::Item: ToPyObject
+{
+    unsafe {
+        let len = iter.len();
+        let ptr = ffi::PyList_New(len as Py_ssize_t);
+        let t = err::cast_from_owned_ptr_or_panic(py, ptr);
+        let mut count = 0;
+        for e in iter {
+            assert!(count < len, "ExactSizeIterator yielded more items than its length");
+            let obj = e.into_py_object(py).into_object();
+            ffi::PyList_SetItem(ptr, count as Py_ssize_t, obj.steal_ptr());
+            count += 1;
+        }
+        if count < len {
+            // Don't leave NULL items behind if the iterator was shorter than announced.
+            err::error_on_minusone(py, ffi::PyList_SetSlice(ptr, count as Py_ssize_t, len as Py_ssize_t, ptr::null_mut()))
+                .expect("failed to truncate list");
         }
+        t
     }
 }
 
@@ -220,4 +422,68 @@ mod test {
         let v2 = list.into_object().extract::<Vec<i32>>(py).unwrap();
         assert_eq!(v, v2);
     }
+
+
+    #[test]
+    fn test_fallible_indexing() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::from_iter(py, vec![1, 2]);
+        assert_eq!(list.try_get_item(py, 1).unwrap().extract::<i32>(py).unwrap(), 2);
+        assert!(list.try_get_item(py, 2).is_err());
+        list.try_set_item(py, 0, 5i32.to_py_object(py).into_object()).unwrap();
+        assert_eq!(list.get_item(py, 0).extract::<i32>(py).unwrap(), 5);
+        let err = list.try_set_item(py, 2, py.None()).unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::IndexError>()));
+    }
+
+    #[test]
+    fn test_append_extend_pop_remove() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::new(py, &[]);
+        list.append(py, 1).unwrap();
+        list.extend(py, &vec![2, 3, 2].to_py_object(py).into_object()).unwrap();
+        list.extend(py, &py.eval("iter([4])", None, None).unwrap()).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<i32>>(py).unwrap(), [1, 2, 3, 2, 4]);
+        assert_eq!(list.pop(py).unwrap().extract::<i32>(py).unwrap(), 4);
+        list.remove(py, 2).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<i32>>(py).unwrap(), [1, 3, 2]);
+        assert!(list.remove(py, 7).is_err());
+        assert!(PyList::new(py, &[]).pop(py).is_err());
+    }
+
+    #[test]
+    fn test_sort_and_reverse() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::from_iter(py, vec![3, 1, 2]);
+        list.sort(py).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<i32>>(py).unwrap(), [1, 2, 3]);
+        list.reverse(py).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<i32>>(py).unwrap(), [3, 2, 1]);
+
+        let list = PyList::from_iter(py, vec!["bb", "a", "ccc", "dd"]);
+        list.sort_by_key(py, |py, item| Ok(item.extract::<String>(py)?.len())).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<String>>(py).unwrap(), ["a", "bb", "dd", "ccc"]);
+        assert!(list.sort_by_key(py, |py, item| item.extract::<i32>(py)).is_err());
+        // mixed types are fine as long as the keys are comparable
+        let list = py.eval("[None, 1, 'x']", None, None).unwrap().cast_into::<PyList>(py).unwrap();
+        list.sort_by_key(py, |_, _| Ok(0)).unwrap();
+        assert!(list.get_item(py, 0) == py.None());
+    }
+
+    #[test]
+    fn test_slices() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::from_iter(py, 0..5);
+        assert_eq!(list.get_slice(py, 1, 3).into_object().extract::<Vec<i32>>(py).unwrap(), [1, 2]);
+        assert_eq!(list.get_slice(py, 3, 100).len(py), 2);
+        list.set_slice(py, 1, 3, &vec![7, 8, 9].to_py_object(py).into_object()).unwrap();
+        assert_eq!(list.as_object().extract::<Vec<i32>>(py).unwrap(), [0, 7, 8, 9, 3, 4]);
+        list.del_slice(py, 0, 4).unwrap();
+        assert_eq!(list.as_tuple(py).into_object().extract::<(i32, i32)>(py).unwrap(), (3, 4));
+        assert!(list.set_slice(py, 0, 1, &py.None()).is_err());
+    }
 }
@@ -0,0 +1,163 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use ffi;
+use python::{Python, PythonObject, PyClone, PyDrop};
+use conversion::ToPyObject;
+use objectprotocol::ObjectProtocol;
+use objects::{PyObject, PyList};
+use err::{self, PyErr, PyResult};
+
+/// Represents a reference to a Python object supporting the mapping protocol.
+///
+/// Note that in Python 3, every object that supports indexing with `[]` is considered
+/// a mapping, including sequences like `list` and `str`.
+pub struct PyMapping(PyObject);
+
+pyobject_newtype!(PyMapping, PyMapping_Check);
+
+impl PyMapping {
+    /// Returns the number of keys in the mapping. This is equivalent to Python `len()`.
+    #[inline]
+    pub fn len(&self, py: Python) -> PyResult<usize> {
+        let v = unsafe { ffi::PyMapping_Size(self.0.as_ptr()) };
+        if v == -1 {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(v as usize)
+        }
+    }
+
+    /// Determines whether the mapping contains the specified key.
+    /// This is equivalent to the Python expression `key in self`.
+    ///
+    /// Unlike `PyMapping_HasKey`, errors raised during the lookup are reported.
+    pub fn contains<K>(&self, py: Python, key: K) -> PyResult<bool> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            match ffi::PySequence_Contains(self.0.as_ptr(), key) {
+                1 => Ok(true),
+                0 => Ok(false),
+                _ => Err(PyErr::fetch(py))
+            }
+        })
+    }
+
+    /// Gets the value of the specified key.
+    /// This is equivalent to the Python expression `self[key]`.
+    #[inline]
+    pub fn get_item<K>(&self, py: Python, key: K) -> PyResult<PyObject> where K: ToPyObject {
+        self.0.get_item(py, key)
+    }
+
+    /// Sets the value of the specified key.
+    /// This is equivalent to the Python expression `self[key] = value`.
+    #[inline]
+    pub fn set_item<K, V>(&self, py: Python, key: K, value: V) -> PyResult<()> where K: ToPyObject, V: ToPyObject {
+        self.0.set_item(py, key, value)
+    }
+
+    /// Deletes the specified key.
+    /// This is equivalent to the Python expression `del self[key]`.
+    #[inline]
+    pub fn del_item<K>(&self, py: Python, key: K) -> PyResult<()> where K: ToPyObject {
+        self.0.del_item(py, key)
+    }
+
+    /// Returns a list of the keys of the mapping.
+    /// This is equivalent to the Python expression `list(self.keys())`.
+    #[inline]
+    pub fn keys(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Keys(self.0.as_ptr())) }
+    }
+
+    /// Returns a list of the values of the mapping.
+    /// This is equivalent to the Python expression `list(self.values())`.
+    #[inline]
+    pub fn values(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Values(self.0.as_ptr())) }
+    }
+
+    /// Returns a list of the (key, value) tuples of the mapping.
+    /// This is equivalent to the Python expression `list(self.items())`.
+    #[inline]
+    pub fn items(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Items(self.0.as_ptr())) }
+    }
+}
+
+// Before Python 3.7, `PyMapping_Keys()` and friends may return any iterable (e.g. a dict view).
+unsafe fn to_list(py: Python, ptr: *mut ffi::PyObject) -> PyResult<PyList> {
+    let obj = err::result_from_owned_ptr(py, ptr)?;
+    if let Ok(list) = obj.cast_as::<PyList>(py) {
+        return Ok(list.clone_ref(py));
+    }
+    let list = err::result_cast_from_owned_ptr(py, ffi::PySequence_List(obj.as_ptr()));
+    obj.release_ref(py);
+    list
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::{PyDict, PyMapping};
+    use objects::tests::{user_dict, extract_sorted};
+
+    #[test]
+    fn test_dict_is_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, "a", 1).unwrap();
+        let mapping = dict.into_object().cast_into::<PyMapping>(py).unwrap();
+        assert_eq!(mapping.len(py).unwrap(), 1);
+        assert!(mapping.contains(py, "a").unwrap());
+        assert!(!mapping.contains(py, "b").unwrap());
+        assert!(mapping.contains(py, PyDict::new(py)).is_err());
+        assert_eq!(mapping.get_item(py, "a").unwrap().extract::<i32>(py).unwrap(), 1);
+        assert!(5i32.to_py_object(py).into_object().cast_into::<PyMapping>(py).is_err());
+    }
+
+    #[test]
+    fn test_keys_values_items() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = user_dict(py, &PyDict::from_pairs(py, vec![("a", 1), ("b", 2)]).unwrap());
+        let mapping = obj.cast_into::<PyMapping>(py).unwrap();
+        assert_eq!(extract_sorted::<String>(py, mapping.keys(py).unwrap().as_object()), ["a", "b"]);
+        assert_eq!(extract_sorted::<i32>(py, mapping.values(py).unwrap().as_object()), [1, 2]);
+        assert_eq!(extract_sorted::<(String, i32)>(py, mapping.items(py).unwrap().as_object()),
+            [("a".to_string(), 1), ("b".to_string(), 2)]);
+        assert!(mapping.get_item(py, "c").is_err());
+        mapping.set_item(py, "c", 3).unwrap();
+        assert_eq!(mapping.len(py).unwrap(), 3);
+
+        #[cfg(feature="python3-sys")]
+        {
+            let obj = py.eval("__import__('types').MappingProxyType({'a': 1})", None, None).unwrap();
+            let mapping = obj.cast_into::<PyMapping>(py).unwrap();
+            assert_eq!(mapping.keys(py).unwrap().len(py), 1);
+            assert!(mapping.set_item(py, "c", 3).is_err());
+        }
+
+        let mapping = PyDict::new(py).into_object().cast_into::<PyMapping>(py).unwrap();
+        mapping.set_item(py, "x", 1).unwrap();
+        mapping.del_item(py, "x").unwrap();
+        assert_eq!(mapping.len(py).unwrap(), 0);
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#[cfg(feature="python3-sys")]
+use libc::c_void;
+use ffi;
+use python::Python;
+#[cfg(feature="python3-sys")]
+use buffer::NativeElement;
+use objectprotocol::ObjectProtocol;
+use objects::PyObject;
+use err::{self, PyResult};
+
+/// Represents a Python `memoryview` object.
+///
+/// A memoryview can expose memory owned by Rust to Python without copying it;
+/// see `PyMemoryView::from_owner()`.
+/// To access the contents of a memoryview (or any other object supporting the buffer protocol)
+/// from Rust, use `buffer::PyBuffer`.
+pub struct PyMemoryView(PyObject);
+
+pyobject_newtype!(PyMemoryView, PyMemoryView_Check, PyMemoryView_Type);
+
+impl PyMemoryView {
+    /// Creates a memoryview of an object that supports the buffer protocol.
+    ///
+    /// Corresponds to `memoryview(obj)` in Python.
+    pub fn from_object(py: Python, obj: &PyObject) -> PyResult<PyMemoryView> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyMemoryView_FromObject(obj.as_ptr()))
+        }
+    }
+
+    /// Creates a read-only memoryview of the elements in `owner`, without copying them.
+    ///
+    /// `owner` can be any type that owns a contiguous array of elements, e.g. `Vec<u8>`,
+    /// `Box<[f32]>` or a memory-mapped file. It is moved into a Python object and dropped
+    /// once the memoryview and all buffers exported from it are released.
+    /// The memoryview uses the native format of `T` (e.g. `'f'` for `f32`).
+    #[cfg(feature="python3-sys")]
+    pub fn from_owner<O, T>(py: Python, owner: O) -> PyMemoryView
+        where O: AsRef<[T]> + Send + 'static, T: NativeElement
+    {
+        let owner = Box::new(owner);
+        let (buf, len) = {
+            let slice = (*owner).as_ref();
+            (slice.as_ptr() as *mut c_void, slice.len())
+        };
+        unsafe { new_memoryview::<T>(py, buf, len, true, owner) }
+    }
+
+    /// Creates a writable memoryview of the elements in `owner`, without copying them.
+    ///
+    /// Changes made through the memoryview are visible in `owner`, which
+    /// is dropped once the memoryview and all buffers exported from it are released.
+    /// See `from_owner()` for details.
+    #[cfg(feature="python3-sys")]
+    pub fn from_owner_mut<O, T>(py: Python, owner: O) -> PyMemoryView
+        where O: AsMut<[T]> + Send + 'static, T: NativeElement
+    {
+        let mut owner = Box::new(owner);
+        let (buf, len) = {
+            let slice = (*owner).as_mut();
+            (slice.as_mut_ptr() as *mut c_void, slice.len())
+        };
+        unsafe { new_memoryview::<T>(py, buf, len, false, owner) }
+    }
+
+    /// Gets whether the memoryview is read-only.
+    ///
+    /// Fails with `ValueError` if the memoryview was released.
+    pub fn is_readonly(&self, py: Python) -> PyResult<bool> {
+        self.0.getattr(py, "readonly")?.extract(py)
+    }
+}
+
+// Exporting Rust-owned memory relies on the Python 3 buffer protocol.
+#[cfg(feature="python3-sys")]
+use self::owned::new_memoryview;
+
+#[cfg(feature="python3-sys")]
+mod owned {
+    use std::{mem, ptr};
+    use libc::{c_char, c_int, c_void};
+    use ffi;
+    use python::{Python, PythonObject, PyDrop};
+    use objects::{PyType, exc};
+    use buffer::NativeElement;
+    use function::AbortOnDrop;
+    use err::{self, PyErr};
+    use super::PyMemoryView;
+
+    // `buf` must point to `len` elements of type `T` owned by `owner`,
+    // and remain valid as long as `owner` isn't dropped.
+    pub unsafe fn new_memoryview<T: NativeElement>(
+        py: Python, buf: *mut c_void, len: usize, readonly: bool, owner: Box<Send>
+    ) -> PyMemoryView {
+        let ty = owned_buffer_type(py);
+        let obj = err::from_owned_ptr_or_panic(py, ffi::PyType_GenericAlloc(ty.as_type_ptr(), 0));
+        ty.release_ref(py);
+        let data = obj.as_ptr() as *mut OwnedBuffer;
+        ptr::write(&mut (*data).buf, buf);
+        ptr::write(&mut (*data).shape, len as ffi::Py_ssize_t);
+        ptr::write(&mut (*data).itemsize, mem::size_of::<T>() as ffi::Py_ssize_t);
+        ptr::write(&mut (*data).format, T::format().as_ptr());
+        ptr::write(&mut (*data).readonly, readonly);
+        ptr::write(&mut (*data).owner, owner);
+        let view = err::cast_from_owned_ptr_or_panic(py, ffi::PyMemoryView_FromObject(obj.as_ptr()));
+        obj.release_ref(py);
+        view
+    }
+
+    /// Python object that exports a buffer owned by a Rust object.
+    #[repr(C)]
+    struct OwnedBuffer {
+        ob_base: ffi::PyObject,
+        buf: *mut c_void,
+        shape: ffi::Py_ssize_t,
+        itemsize: ffi::Py_ssize_t,
+        format: *const c_char,
+        readonly: bool,
+        owner: Box<Send>,
+    }
+
+    static mut OWNED_BUFFER_PROCS: ffi::PyBufferProcs = ffi::PyBufferProcs {
+        bf_getbuffer: Some(owned_buffer_getbuffer),
+        bf_releasebuffer: None,
+    };
+
+    static mut OWNED_BUFFER_TYPE: ffi::PyTypeObject = ffi::PyTypeObject_INIT;
+
+    fn owned_buffer_type(py: Python) -> PyType {
+        let name = cstr!("cpython.OwnedBuffer");
+        unsafe {
+            if !::py_class::is_ready(py, &OWNED_BUFFER_TYPE) {
+                OWNED_BUFFER_TYPE.tp_name = name.as_ptr();
+                OWNED_BUFFER_TYPE.tp_basicsize = mem::size_of::<OwnedBuffer>() as ffi::Py_ssize_t;
+                OWNED_BUFFER_TYPE.tp_dealloc = Some(owned_buffer_dealloc);
+                OWNED_BUFFER_TYPE.tp_flags = ffi::Py_TPFLAGS_DEFAULT;
+                OWNED_BUFFER_TYPE.tp_as_buffer = &mut OWNED_BUFFER_PROCS;
+                if ffi::PyType_Ready(&mut OWNED_BUFFER_TYPE) != 0 {
+                    PyErr::fetch(py).print(py);
+                    panic!("failed to initialize cpython.OwnedBuffer type");
+                }
+            }
+            PyType::from_type_ptr(py, &mut OWNED_BUFFER_TYPE)
+        }
+    }
+
+    extern "C" fn owned_buffer_getbuffer(obj: *mut ffi::PyObject, view: *mut ffi::Py_buffer, flags: c_int) -> c_int {
+        unsafe {
+            let data = obj as *mut OwnedBuffer;
+            if (flags & ffi::PyBUF_WRITABLE) != 0 && (*data).readonly {
+                let py = Python::assume_gil_acquired();
+                PyErr::new::<exc::BufferError, _>(py, "buffer is read-only").restore(py);
+                (*view).obj = ptr::null_mut();
+                return -1;
+            }
+            ffi::Py_INCREF(obj);
+            (*view).obj = obj;
+            (*view).buf = (*data).buf;
+            (*view).len = (*data).shape * (*data).itemsize;
+            (*view).itemsize = (*data).itemsize;
+            (*view).readonly = (*data).readonly as c_int;
+            (*view).ndim = 1;
+            (*view).format = if (flags & ffi::PyBUF_FORMAT) != 0 {
+                (*data).format as *mut _
+            } else {
+                ptr::null_mut()
+            };
+            (*view).shape = if (flags & ffi::PyBUF_ND) != 0 {
+                &mut (*data).shape
+            } else {
+                ptr::null_mut()
+            };
+            (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+                &mut (*data).itemsize
+            } else {
+                ptr::null_mut()
+            };
+            (*view).suboffsets = ptr::null_mut();
+            (*view).internal = ptr::null_mut();
+            0
+        }
+    }
+
+    unsafe extern "C" fn owned_buffer_dealloc(obj: *mut ffi::PyObject) {
+        let guard = AbortOnDrop("cpython.OwnedBuffer dealloc");
+        let data = obj as *mut OwnedBuffer;
+        drop(ptr::read(&(*data).owner));
+        ffi::PyObject_Free(obj as *mut c_void);
+        mem::forget(guard);
+    }
+}
+
+#[cfg(all(test, feature="python3-sys"))]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use python::{Python, PythonObject, PyDrop};
+    use objects::PyDict;
+    use buffer::PyBuffer;
+    use super::PyMemoryView;
+
+    #[test]
+    fn test_from_owner() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = PyMemoryView::from_owner(py, vec![1.0f32, 2.0, 3.5]);
+        assert!(view.is_readonly(py).unwrap());
+        let d = PyDict::new(py);
+        d.set_item(py, "view", &view).unwrap();
+        py.run("assert view.format == 'f' and view.tolist() == [1.0, 2.0, 3.5]", None, Some(&d)).unwrap();
+        py.run("try:\n  view[0] = 5.0\nexcept TypeError: pass\nelse: assert False", None, Some(&d)).unwrap();
+
+        let buffer = PyBuffer::get(py, view.as_object()).unwrap();
+        assert_eq!(buffer.to_vec::<f32>(py).unwrap(), [1.0, 2.0, 3.5]);
+    }
+
+    #[test]
+    fn test_from_owner_mut() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data: Box<[u8]> = vec![0u8; 4].into_boxed_slice();
+        let view = PyMemoryView::from_owner_mut(py, data);
+        assert!(!view.is_readonly(py).unwrap());
+        let d = PyDict::new(py);
+        d.set_item(py, "view", &view).unwrap();
+        py.run("view[1] = 42; view[2:4] = b'ab'; assert bytes(view) == b'\\x00*ab'", None, Some(&d)).unwrap();
+    }
+
+    #[test]
+    fn test_owner_dropped() {
+        struct Owner(Vec<u8>, Arc<AtomicBool>);
+        impl AsRef<[u8]> for Owner {
+            fn as_ref(&self) -> &[u8] { &self.0 }
+        }
+        impl Drop for Owner {
+            fn drop(&mut self) { self.1.store(true, Ordering::Relaxed) }
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let view = PyMemoryView::from_owner(py, Owner(b"abc".to_vec(), dropped.clone()));
+        let buffer = PyBuffer::get(py, view.as_object()).unwrap();
+        drop(view);
+        assert!(!dropped.load(Ordering::Relaxed));
+        assert_eq!(buffer.to_vec::<u8>(py).unwrap(), b"abc");
+        buffer.release_ref(py);
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+}
@@ -20,7 +20,7 @@ pub use self::object::PyObject;
 pub use self::typeobject::PyType;
 pub use self::module::PyModule;
 
-pub use self::string::{PyBytes, PyString, PyStringData};
+pub use self::string::{PyBytes, PyByteArray, PyString, PyStringData};
 #[cfg(feature="python27-sys")]
 pub use self::string::PyUnicode;
 #[cfg(feature="python3-sys")]
@@ -29,15 +29,23 @@ pub use self::string::PyString as PyUnicode;
 pub use self::iterator::PyIterator;
 pub use self::boolobject::PyBool;
 pub use self::tuple::{PyTuple, NoArgs};
-pub use self::dict::PyDict;
-pub use self::set::PySet;
+pub use self::dict::{PyDict, PyDictItems, PyDictKeys, PyDictValues};
+pub use self::set::{PySet, PyFrozenSet};
 pub use self::list::PyList;
 #[cfg(feature="python27-sys")]
 pub use self::num::PyInt;
 #[cfg(feature="python3-sys")]
 pub use self::num::PyLong as PyInt;
-pub use self::num::{PyLong, PyFloat};
+pub use self::num::{PyLong, PyFloat, PyComplex};
 pub use self::sequence::PySequence;
+pub use self::mapping::PyMapping;
+pub use self::number::PyNumber;
+pub use self::capsule::PyCapsule;
+pub use self::weakref::{PyWeakRef, PyWeakProxy};
+pub use self::slice::{PySlice, PySliceIndices, SliceOrIndex};
+pub use self::memoryview::PyMemoryView;
+#[cfg(all(feature="python3-sys", not(Py_LIMITED_API)))]
+pub use self::datetime::{PyDate, PyDateTime, PyTime, PyDelta, PyTzInfo};
 
 #[macro_export(local_inner_macros)]
 macro_rules! pyobject_newtype(
@@ -134,6 +142,15 @@ mod tuple;
 mod list;
 mod num;
 mod sequence;
+mod mapping;
+mod number;
+mod capsule;
+mod weakref;
+mod slice;
+mod memoryview;
+mod path;
+#[cfg(all(feature="python3-sys", not(Py_LIMITED_API)))]
+mod datetime;
 pub mod exc;
 
 #[cfg(feature="python27-sys")]
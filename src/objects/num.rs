@@ -19,7 +19,7 @@
 extern crate num_traits;
 
 use self::num_traits::cast::cast;
-use libc::{c_long, c_double};
+use libc::{c_int, c_long, c_double};
 use python::{Python, PythonObject, PyClone};
 use err::{self, PyResult, PyErr};
 use super::object::PyObject;
@@ -63,6 +63,15 @@ pyobject_newtype!(PyLong, PyLong_Check, PyLong_Type);
 pub struct PyFloat(PyObject);
 pyobject_newtype!(PyFloat, PyFloat_Check, PyFloat_Type);
 
+/// Represents a Python `complex` object.
+///
+/// When the `num-complex` feature is enabled, you can usually avoid
+/// directly working with this type by using [ToPyObject](trait.ToPyObject.html)
+/// and [extract](struct.PyObject.html#method.extract)
+/// with `num_complex::Complex<f32>`/`Complex<f64>`.
+pub struct PyComplex(PyObject);
+pyobject_newtype!(PyComplex, PyComplex_Check, PyComplex_Type);
+
 #[cfg(feature="python27-sys")]
 impl PyInt {
     /// Creates a new Python 2.7 `int` object.
@@ -102,6 +111,25 @@ impl PyFloat {
     }
 }
 
+impl PyComplex {
+    /// Creates a new Python `complex` object.
+    pub fn new(py: Python, real: c_double, imag: c_double) -> PyComplex {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PyComplex_FromDoubles(real, imag))
+        }
+    }
+
+    /// Gets the real part of this complex number.
+    pub fn real(&self, _py: Python) -> c_double {
+        unsafe { ffi::PyComplex_RealAsDouble(self.0.as_ptr()) }
+    }
+
+    /// Gets the imaginary part of this complex number.
+    pub fn imag(&self, _py: Python) -> c_double {
+        unsafe { ffi::PyComplex_ImagAsDouble(self.0.as_ptr()) }
+    }
+}
+
 macro_rules! int_fits_c_long(
     ($rust_type:ty) => (
         #[cfg(feature="python27-sys")]
@@ -268,6 +296,56 @@ int_fits_larger_int!(usize, u64);
 // u64 has a manual implementation as it never fits into signed long
 int_convert_u64_or_i64!(u64, ffi::PyLong_FromUnsignedLongLong, ffi::PyLong_AsUnsignedLongLong);
 
+// Converts a little-endian two's complement (if `is_signed`) or unsigned integer to a Python long.
+fn long_from_bytes(py: Python, bytes: &[u8], is_signed: bool) -> PyLong {
+    unsafe {
+        err::cast_from_owned_ptr_or_panic(py,
+            ffi::_PyLong_FromByteArray(bytes.as_ptr(), bytes.len(), 1, is_signed as c_int))
+    }
+}
+
+// Converts `obj` to a Python long, like the other integer conversions do.
+fn to_long(py: Python, obj: &PyObject) -> PyResult<PyObject> {
+    unsafe {
+        if ffi::PyLong_Check(obj.as_ptr()) != 0 {
+            Ok(obj.clone_ref(py))
+        } else {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Long(obj.as_ptr()))
+        }
+    }
+}
+
+// Converts a Python long to a little-endian two's complement (if `is_signed`) or unsigned integer.
+// Fails with `OverflowError` if the value doesn't fit into `bytes`.
+fn long_to_bytes(py: Python, num: &PyObject, bytes: &mut [u8], is_signed: bool) -> PyResult<()> {
+    unsafe {
+        err::error_on_minusone(py, ffi::_PyLong_AsByteArray(num.as_ptr() as *mut ffi::PyLongObject,
+            bytes.as_mut_ptr(), bytes.len(), 1, is_signed as c_int))
+    }
+}
+
+macro_rules! int_convert_128 (
+    ($rust_type:ty, $is_signed:expr) => (
+        impl ToPyObject for $rust_type {
+            type ObjectType = PyLong;
+
+            fn to_py_object(&self, py: Python) -> PyLong {
+                long_from_bytes(py, &self.to_le_bytes(), $is_signed)
+            }
+        }
+
+        extract!(obj to $rust_type; py => {
+            let num = to_long(py, obj)?;
+            let mut bytes = [0u8; 16];
+            long_to_bytes(py, &num, &mut bytes, $is_signed)?;
+            Ok(<$rust_type>::from_le_bytes(bytes))
+        });
+    )
+);
+
+int_convert_128!(i128, true);
+int_convert_128!(u128, false);
+
 impl ToPyObject for f64 {
     type ObjectType = PyFloat;
 
@@ -301,11 +379,119 @@ extract!(obj to f32; py => {
     Ok(obj.extract::<f64>(py)? as f32)
 });
 
+#[cfg(feature="num-complex")]
+mod complex {
+    use num_complex::Complex;
+    use libc::c_double;
+    use python::{Python, PythonObject};
+    use err::{PyErr, PyResult};
+    use super::super::object::PyObject;
+    use conversion::ToPyObject;
+    use ffi;
+    use super::PyComplex;
+
+    // Like the `D` format of `PyArg_ParseTuple`: accepts `complex`, and objects implementing
+    // `__complex__`, `__float__` or `__index__`, but does not parse strings.
+    fn to_complex(py: Python, obj: &PyObject) -> PyResult<(c_double, c_double)> {
+        unsafe {
+            let re = ffi::PyComplex_RealAsDouble(obj.as_ptr());
+            if re == -1.0 && PyErr::occurred(py) {
+                return Err(PyErr::fetch(py));
+            }
+            let im = ffi::PyComplex_ImagAsDouble(obj.as_ptr());
+            if im == -1.0 && PyErr::occurred(py) {
+                return Err(PyErr::fetch(py));
+            }
+            Ok((re, im))
+        }
+    }
+
+    impl ToPyObject for Complex<f64> {
+        type ObjectType = PyComplex;
+
+        fn to_py_object(&self, py: Python) -> PyComplex {
+            PyComplex::new(py, self.re, self.im)
+        }
+    }
+
+    extract!(obj to Complex<f64>; py => {
+        let (re, im) = to_complex(py, obj)?;
+        Ok(Complex::new(re, im))
+    });
+
+    impl ToPyObject for Complex<f32> {
+        type ObjectType = PyComplex;
+
+        fn to_py_object(&self, py: Python) -> PyComplex {
+            PyComplex::new(py, self.re as f64, self.im as f64)
+        }
+    }
+
+    extract!(obj to Complex<f32>; py => {
+        let (re, im) = to_complex(py, obj)?;
+        Ok(Complex::new(re as f32, im as f32))
+    });
+}
+
+#[cfg(feature="num-bigint")]
+mod bigint {
+    use std::cmp;
+    use num_bigint::{BigInt, BigUint};
+    use python::Python;
+    use err::{PyResult, PyErr};
+    use super::super::object::PyObject;
+    use conversion::ToPyObject;
+    use ffi;
+    use super::{PyLong, to_long, long_from_bytes, long_to_bytes};
+
+    impl ToPyObject for BigInt {
+        type ObjectType = PyLong;
+
+        fn to_py_object(&self, py: Python) -> PyLong {
+            long_from_bytes(py, &self.to_signed_bytes_le(), true)
+        }
+    }
+
+    impl ToPyObject for BigUint {
+        type ObjectType = PyLong;
+
+        fn to_py_object(&self, py: Python) -> PyLong {
+            long_from_bytes(py, &self.to_bytes_le(), false)
+        }
+    }
+
+    // Gets the number of bytes required to store the value of the Python long `num`.
+    fn byte_len(py: Python, num: &PyObject, is_signed: bool) -> PyResult<usize> {
+        let bits = unsafe { ffi::_PyLong_NumBits(num.as_ptr()) };
+        if bits == !0 {
+            return Err(PyErr::fetch(py));
+        }
+        // signed values need an additional bit for the sign
+        Ok(if is_signed { bits / 8 + 1 } else { cmp::max(1, (bits + 7) / 8) })
+    }
+
+    extract!(obj to BigInt; py => {
+        let num = to_long(py, obj)?;
+        let mut bytes = vec![0u8; byte_len(py, &num, true)?];
+        long_to_bytes(py, &num, &mut bytes, true)?;
+        Ok(BigInt::from_signed_bytes_le(&bytes))
+    });
+
+    extract!(obj to BigUint; py => {
+        let num = to_long(py, obj)?;
+        let mut bytes = vec![0u8; byte_len(py, &num, false)?];
+        long_to_bytes(py, &num, &mut bytes, false)?;
+        Ok(BigUint::from_bytes_le(&bytes))
+    });
+}
+
 #[cfg(test)]
 mod test {
     use std;
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::exc;
 
     macro_rules! num_to_py_object_and_back (
         ($func_name:ident, $t1:ty, $t2:ty) => (
@@ -381,4 +567,87 @@ mod test {
         assert_eq!(v, obj.extract::<u64>(py).unwrap());
         assert!(obj.extract::<i64>(py).is_err());
     }
+
+    #[test]
+    fn test_complex() {
+        use objects::PyComplex;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let c = PyComplex::new(py, 1.5, -2.0);
+        assert_eq!(c.real(py), 1.5);
+        assert_eq!(c.imag(py), -2.0);
+        let obj = py.eval("complex(3, 4)", None, None).unwrap();
+        let c = obj.cast_as::<PyComplex>(py).unwrap();
+        assert_eq!((c.real(py), c.imag(py)), (3.0, 4.0));
+    }
+
+    #[test]
+    #[cfg(feature="num-complex")]
+    fn test_num_complex() {
+        use num_complex::Complex;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = Complex::new(1.5f64, -2.0).to_py_object(py).into_object();
+        assert_eq!(obj.extract::<Complex<f64>>(py).unwrap(), Complex::new(1.5, -2.0));
+        assert_eq!(obj.extract::<Complex<f32>>(py).unwrap(), Complex::new(1.5f32, -2.0));
+        let obj = 3i32.to_py_object(py).into_object();
+        assert_eq!(obj.extract::<Complex<f64>>(py).unwrap(), Complex::new(3.0, 0.0));
+        let obj = 2.5f64.to_py_object(py).into_object();
+        assert_eq!(obj.extract::<Complex<f64>>(py).unwrap(), Complex::new(2.5, 0.0));
+        assert!("abc".to_py_object(py).into_object().extract::<Complex<f64>>(py).is_err());
+        let err = "1+2j".to_py_object(py).into_object().extract::<Complex<f64>>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+    }
+
+    #[test]
+    fn test_i128() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for &v in &[0i128, -1, std::i128::MAX, std::i128::MIN, 1 << 100] {
+            let obj = v.to_py_object(py).into_object();
+            assert_eq!(obj.extract::<i128>(py).unwrap(), v);
+        }
+        let obj = py.eval("-2**127", None, None).unwrap();
+        assert_eq!(obj.extract::<i128>(py).unwrap(), std::i128::MIN);
+        let obj = py.eval("2**127", None, None).unwrap();
+        assert!(obj.extract::<i128>(py).unwrap_err().matches(py, py.get_type::<exc::OverflowError>()));
+        assert_eq!(obj.extract::<u128>(py).unwrap(), 1 << 127);
+    }
+
+    #[test]
+    fn test_u128() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = std::u128::MAX.to_py_object(py).into_object();
+        assert!(obj.compare(py, py.eval("2**128 - 1", None, None).unwrap()).unwrap() == std::cmp::Ordering::Equal);
+        assert_eq!(obj.extract::<u128>(py).unwrap(), std::u128::MAX);
+        assert!(obj.extract::<i128>(py).is_err());
+        let obj = py.eval("2**128", None, None).unwrap();
+        assert!(obj.extract::<u128>(py).is_err());
+        let obj = (-1i32).to_py_object(py).into_object();
+        assert!(obj.extract::<u128>(py).unwrap_err().matches(py, py.get_type::<exc::OverflowError>()));
+    }
+
+    #[test]
+    #[cfg(feature="num-bigint")]
+    fn test_bigint() {
+        use num_bigint::{BigInt, BigUint};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("-3**200", None, None).unwrap();
+        let v = obj.extract::<BigInt>(py).unwrap();
+        assert_eq!(v, -super::num_traits::pow(BigInt::from(3), 200));
+        assert!(v.to_py_object(py).into_object().compare(py, &obj).unwrap() == std::cmp::Ordering::Equal);
+        assert!(obj.extract::<BigUint>(py).is_err());
+        for &s in &["0", "-1", "127", "128", "-128", "-129", "255", "256", "2**64"] {
+            let obj = py.eval(s, None, None).unwrap();
+            let v = obj.extract::<BigInt>(py).unwrap();
+            assert!(v.to_py_object(py).into_object().compare(py, &obj).unwrap() == std::cmp::Ordering::Equal, "{}", s);
+        }
+        let obj = py.eval("2**100 + 255", None, None).unwrap();
+        let v = obj.extract::<BigUint>(py).unwrap();
+        assert_eq!(v, super::num_traits::pow(BigUint::from(2u32), 100) + BigUint::from(255u32));
+        assert!(v.to_py_object(py).into_object().compare(py, &obj).unwrap() == std::cmp::Ordering::Equal);
+        assert_eq!(py.eval("0", None, None).unwrap().extract::<BigUint>(py).unwrap(), BigUint::from(0u32));
+    }
 }
@@ -0,0 +1,239 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use ffi;
+use python::{Python, PythonObject};
+use conversion::ToPyObject;
+use objects::{PyObject, PyFloat};
+use err::{self, PyResult};
+
+/// Represents a reference to a Python object supporting the number protocol,
+/// such as `int`, `float`, `decimal.Decimal` or `numpy.float64`.
+///
+/// The binary operations accept any object as the right operand and, like the
+/// corresponding Python operators, fall back to the reflected method of `other`
+/// (e.g. `other.__radd__(self)`).
+pub struct PyNumber(PyObject);
+
+pyobject_newtype!(PyNumber, PyNumber_Check);
+
+impl PyNumber {
+    /// Equivalent to the Python expression `self + other`.
+    #[inline]
+    pub fn add<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Add)
+    }
+
+    /// Equivalent to the Python expression `self - other`.
+    #[inline]
+    pub fn subtract<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Subtract)
+    }
+
+    /// Equivalent to the Python expression `self * other`.
+    #[inline]
+    pub fn multiply<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Multiply)
+    }
+
+    /// Equivalent to the Python expression `self / other` with true division
+    /// (the default in Python 3).
+    #[inline]
+    pub fn true_divide<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_TrueDivide)
+    }
+
+    /// Equivalent to the Python expression `self // other`.
+    #[inline]
+    pub fn floor_divide<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_FloorDivide)
+    }
+
+    /// Equivalent to the Python expression `self % other`.
+    #[inline]
+    pub fn remainder<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Remainder)
+    }
+
+    /// Equivalent to the Python expression `divmod(self, other)`.
+    #[inline]
+    pub fn divmod<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Divmod)
+    }
+
+    /// Equivalent to the Python expression `self << other`.
+    #[inline]
+    pub fn lshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Lshift)
+    }
+
+    /// Equivalent to the Python expression `self >> other`.
+    #[inline]
+    pub fn rshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Rshift)
+    }
+
+    /// Equivalent to the Python expression `self & other`.
+    #[inline]
+    pub fn bitand<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_And)
+    }
+
+    /// Equivalent to the Python expression `self | other`.
+    #[inline]
+    pub fn bitor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Or)
+    }
+
+    /// Equivalent to the Python expression `self ^ other`.
+    #[inline]
+    pub fn bitxor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        binary_op(py, &self.0, other, ffi::PyNumber_Xor)
+    }
+
+    /// Equivalent to the Python expression `self ** exponent`.
+    pub fn power<O>(&self, py: Python, exponent: O) -> PyResult<PyObject> where O: ToPyObject {
+        exponent.with_borrowed_ptr(py, |exponent| unsafe {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Power(self.0.as_ptr(), exponent, ffi::Py_None()))
+        })
+    }
+
+    /// Equivalent to the Python expression `pow(self, exponent, modulus)`.
+    pub fn power_mod<O, M>(&self, py: Python, exponent: O, modulus: M) -> PyResult<PyObject>
+        where O: ToPyObject, M: ToPyObject
+    {
+        exponent.with_borrowed_ptr(py, |exponent| modulus.with_borrowed_ptr(py, |modulus| unsafe {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Power(self.0.as_ptr(), exponent, modulus))
+        }))
+    }
+
+    /// Equivalent to the Python expression `-self`.
+    #[inline]
+    pub fn negative(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Negative(self.0.as_ptr())) }
+    }
+
+    /// Equivalent to the Python expression `+self`.
+    #[inline]
+    pub fn positive(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Positive(self.0.as_ptr())) }
+    }
+
+    /// Equivalent to the Python expression `abs(self)`.
+    #[inline]
+    pub fn absolute(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Absolute(self.0.as_ptr())) }
+    }
+
+    /// Equivalent to the Python expression `~self`.
+    #[inline]
+    pub fn invert(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Invert(self.0.as_ptr())) }
+    }
+
+    /// Converts the number to an integer without loss of precision, using `__index__()`.
+    /// This is equivalent to the Python expression `operator.index(self)`.
+    ///
+    /// Fails with `TypeError` for objects like `float` that aren't integers.
+    #[inline]
+    pub fn index(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Index(self.0.as_ptr())) }
+    }
+
+    /// Converts the number to an integer, truncating floats towards zero.
+    /// This is equivalent to the Python expression `int(self)`.
+    #[inline]
+    pub fn to_int(&self, py: Python) -> PyResult<PyObject> {
+        unsafe { err::result_from_owned_ptr(py, ffi::PyNumber_Long(self.0.as_ptr())) }
+    }
+
+    /// Converts the number to a float.
+    /// This is equivalent to the Python expression `float(self)`.
+    #[inline]
+    pub fn to_float(&self, py: Python) -> PyResult<PyFloat> {
+        unsafe { err::result_cast_from_owned_ptr(py, ffi::PyNumber_Float(self.0.as_ptr())) }
+    }
+}
+
+fn binary_op<O>(py: Python, obj: &PyObject, other: O,
+                f: unsafe extern "C" fn(*mut ffi::PyObject, *mut ffi::PyObject) -> *mut ffi::PyObject)
+    -> PyResult<PyObject> where O: ToPyObject
+{
+    other.with_borrowed_ptr(py, |other| unsafe {
+        err::result_from_owned_ptr(py, f(obj.as_ptr(), other))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::{PyNumber, PyList};
+
+    fn number(py: Python, expr: &str) -> PyNumber {
+        py.eval(expr, None, None).unwrap().cast_into::<PyNumber>(py).unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert!(5i32.to_py_object(py).into_object().cast_into::<PyNumber>(py).is_ok());
+        assert!(py.eval("__import__('decimal').Decimal('1.5')", None, None).unwrap().cast_into::<PyNumber>(py).is_ok());
+        assert!("5".to_py_object(py).into_object().cast_into::<PyNumber>(py).is_err());
+        assert!(PyList::new(py, &[]).into_object().cast_into::<PyNumber>(py).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let seven = number(py, "7");
+        assert_eq!(seven.add(py, 3).unwrap().extract::<i32>(py).unwrap(), 10);
+        assert_eq!(seven.subtract(py, 10).unwrap().extract::<i32>(py).unwrap(), -3);
+        assert_eq!(seven.multiply(py, 2.5).unwrap().extract::<f64>(py).unwrap(), 17.5);
+        assert_eq!(seven.true_divide(py, 2).unwrap().extract::<f64>(py).unwrap(), 3.5);
+        assert_eq!(seven.floor_divide(py, 2).unwrap().extract::<i32>(py).unwrap(), 3);
+        assert_eq!(seven.remainder(py, -2).unwrap().extract::<i32>(py).unwrap(), -1);
+        assert_eq!(seven.divmod(py, 2).unwrap().extract::<(i32, i32)>(py).unwrap(), (3, 1));
+        assert_eq!(seven.power(py, 2).unwrap().extract::<i32>(py).unwrap(), 49);
+        assert_eq!(seven.power_mod(py, 2, 5).unwrap().extract::<i32>(py).unwrap(), 4);
+        assert_eq!(seven.lshift(py, 1).unwrap().extract::<i32>(py).unwrap(), 14);
+        assert_eq!(seven.rshift(py, 1).unwrap().extract::<i32>(py).unwrap(), 3);
+        assert_eq!(seven.bitand(py, 3).unwrap().extract::<i32>(py).unwrap(), 3);
+        assert_eq!(seven.bitor(py, 8).unwrap().extract::<i32>(py).unwrap(), 15);
+        assert_eq!(seven.bitxor(py, 1).unwrap().extract::<i32>(py).unwrap(), 6);
+        assert_eq!(seven.negative(py).unwrap().extract::<i32>(py).unwrap(), -7);
+        assert_eq!(seven.positive(py).unwrap().extract::<i32>(py).unwrap(), 7);
+        assert_eq!(number(py, "-7").absolute(py).unwrap().extract::<i32>(py).unwrap(), 7);
+        assert_eq!(seven.invert(py).unwrap().extract::<i32>(py).unwrap(), -8);
+        assert!(seven.true_divide(py, 0).is_err());
+        assert!(seven.add(py, "x").is_err());
+    }
+
+    #[test]
+    fn test_conversions() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let x = number(py, "2.75");
+        assert_eq!(x.to_int(py).unwrap().extract::<i32>(py).unwrap(), 2);
+        assert!(x.index(py).is_err());
+        assert_eq!(number(py, "True").index(py).unwrap().extract::<i32>(py).unwrap(), 1);
+        assert_eq!(number(py, "__import__('fractions').Fraction(3, 4)").to_float(py).unwrap().value(py), 0.75);
+    }
+}
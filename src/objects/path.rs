@@ -0,0 +1,308 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between Rust file system paths and Python `str`, `bytes` and `os.PathLike` objects.
+//!
+//! In Python 3, file names are decoded using the file system encoding with the
+//! `surrogateescape` error handler, so file names that aren't valid in that encoding
+//! round-trip losslessly between Rust and Python.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use ffi;
+use python::{Python, PythonObject, PyDrop};
+use conversion::{ToPyObject, FromPyObject, RefFromPyObject};
+use objects::PyObject;
+use err::PyResult;
+
+/// Converts an `OsStr` to a Python `str`, using the file system encoding.
+/// In Python 2, the result is a byte string on Unix.
+impl ToPyObject for OsStr {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        os_str_to_object(py, self)
+    }
+}
+
+/// Converts an `OsString` to a Python `str`, using the file system encoding.
+impl ToPyObject for OsString {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        os_str_to_object(py, self)
+    }
+}
+
+/// Converts a `Path` to a Python `str`, using the file system encoding.
+impl ToPyObject for Path {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        os_str_to_object(py, self.as_os_str())
+    }
+}
+
+/// Converts a `PathBuf` to a Python `str`, using the file system encoding.
+impl ToPyObject for PathBuf {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        os_str_to_object(py, self.as_os_str())
+    }
+}
+
+/// Allows extracting an `OsString` from a Python `str`, `bytes` or `os.PathLike` object.
+/// `os.PathLike` objects (like `pathlib.Path`) are only supported in Python 3.6 and later.
+impl <'source> FromPyObject<'source> for OsString {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        object_to_os_string(py, obj)
+    }
+}
+
+/// Allows extracting a `PathBuf` from a Python `str`, `bytes` or `os.PathLike` object.
+/// `os.PathLike` objects (like `pathlib.Path`) are only supported in Python 3.6 and later.
+impl <'source> FromPyObject<'source> for PathBuf {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        object_to_os_string(py, obj).map(PathBuf::from)
+    }
+}
+
+impl RefFromPyObject for OsStr {
+    fn with_extracted<F, R>(py: Python, obj: &PyObject, f: F) -> PyResult<R>
+        where F: FnOnce(&OsStr) -> R
+    {
+        let s = object_to_os_string(py, obj)?;
+        Ok(f(&s))
+    }
+}
+
+impl RefFromPyObject for Path {
+    fn with_extracted<F, R>(py: Python, obj: &PyObject, f: F) -> PyResult<R>
+        where F: FnOnce(&Path) -> R
+    {
+        let s = object_to_os_string(py, obj)?;
+        Ok(f(Path::new(&s)))
+    }
+}
+
+#[cfg(all(unix, feature="python3-sys"))]
+fn os_str_to_object(py: Python, s: &OsStr) -> PyObject {
+    use std::os::unix::ffi::OsStrExt;
+    let bytes = s.as_bytes();
+    unsafe {
+        ::err::from_owned_ptr_or_panic(py, ffi::PyUnicode_DecodeFSDefaultAndSize(
+            bytes.as_ptr() as *const _, bytes.len() as ffi::Py_ssize_t))
+    }
+}
+
+#[cfg(all(windows, feature="python3-sys"))]
+fn os_str_to_object(py: Python, s: &OsStr) -> PyObject {
+    use std::os::windows::ffi::OsStrExt;
+    let wide: Vec<u16> = s.encode_wide().collect();
+    unsafe {
+        ::err::from_owned_ptr_or_panic(py, ffi::PyUnicode_FromWideChar(
+            wide.as_ptr(), wide.len() as ffi::Py_ssize_t))
+    }
+}
+
+#[cfg(all(unix, feature="python27-sys"))]
+fn os_str_to_object(py: Python, s: &OsStr) -> PyObject {
+    use std::os::unix::ffi::OsStrExt;
+    ::objects::PyBytes::new(py, s.as_bytes()).into_object()
+}
+
+#[cfg(all(windows, feature="python27-sys"))]
+fn os_str_to_object(py: Python, s: &OsStr) -> PyObject {
+    ::objects::PyUnicode::new(py, &s.to_string_lossy()).into_object()
+}
+
+#[cfg(feature="python3-sys")]
+fn object_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    use objects::PyBytes;
+    let path = fspath(py, obj)?;
+    let result = if path.cast_as::<PyBytes>(py).is_ok() {
+        bytes_to_os_string(py, &path)
+    } else {
+        unicode_to_os_string(py, &path)
+    };
+    path.release_ref(py);
+    result
+}
+
+// Equivalent to `os.fspath(obj)`.
+#[cfg(all(feature="python3-sys", Py_3_6))]
+fn fspath(py: Python, obj: &PyObject) -> PyResult<PyObject> {
+    unsafe { ::err::result_from_owned_ptr(py, ffi::PyOS_FSPath(obj.as_ptr())) }
+}
+
+#[cfg(all(feature="python3-sys", not(Py_3_6)))]
+fn fspath(py: Python, obj: &PyObject) -> PyResult<PyObject> {
+    use python::PyClone;
+    use objects::{PyString, PyBytes, exc};
+    if obj.cast_as::<PyString>(py).is_ok() || obj.cast_as::<PyBytes>(py).is_ok() {
+        Ok(obj.clone_ref(py))
+    } else {
+        Err(::err::PyErr::new::<exc::TypeError, _>(py, "expected str or bytes object"))
+    }
+}
+
+#[cfg(all(unix, feature="python3-sys"))]
+fn unicode_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    let bytes = unsafe {
+        ::err::result_from_owned_ptr(py, ffi::PyUnicode_EncodeFSDefault(obj.as_ptr()))?
+    };
+    let result = bytes_to_os_string(py, &bytes);
+    bytes.release_ref(py);
+    result
+}
+
+#[cfg(all(windows, feature="python3-sys"))]
+fn unicode_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    use std::slice;
+    use std::os::windows::ffi::OsStringExt;
+    unsafe {
+        let mut size: ffi::Py_ssize_t = 0;
+        let wide = ffi::PyUnicode_AsWideCharString(obj.as_ptr(), &mut size);
+        if wide.is_null() {
+            return Err(::err::PyErr::fetch(py));
+        }
+        let s = OsString::from_wide(slice::from_raw_parts(wide, size as usize));
+        ffi::PyMem_Free(wide as *mut _);
+        Ok(s)
+    }
+}
+
+#[cfg(all(unix, feature="python3-sys"))]
+fn bytes_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    use objects::PyBytes;
+    Ok(OsString::from_vec(obj.cast_as::<PyBytes>(py)?.data(py).to_vec()))
+}
+
+#[cfg(all(windows, feature="python3-sys"))]
+fn bytes_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    use objects::PyBytes;
+    let data = obj.cast_as::<PyBytes>(py)?.data(py);
+    let s = unsafe {
+        ::err::result_from_owned_ptr(py, ffi::PyUnicode_DecodeFSDefaultAndSize(
+            data.as_ptr() as *const _, data.len() as ffi::Py_ssize_t))?
+    };
+    let result = unicode_to_os_string(py, &s);
+    s.release_ref(py);
+    result
+}
+
+#[cfg(feature="python27-sys")]
+fn object_to_os_string(py: Python, obj: &PyObject) -> PyResult<OsString> {
+    use std::ffi::CStr;
+    use objects::{PyBytes, PyUnicode};
+    use objectprotocol::ObjectProtocol;
+    if let Ok(bytes) = obj.cast_as::<PyBytes>(py) {
+        return bytes_to_os_string(py, bytes.data(py));
+    }
+    obj.cast_as::<PyUnicode>(py)?;
+    let encoded = unsafe {
+        if ffi::Py_FileSystemDefaultEncoding.is_null() {
+            obj.call_method(py, "encode", ::NoArgs, None)?
+        } else {
+            let encoding = CStr::from_ptr(ffi::Py_FileSystemDefaultEncoding).to_string_lossy();
+            obj.call_method(py, "encode", (&*encoding,), None)?
+        }
+    };
+    let result = bytes_to_os_string(py, encoded.cast_as::<PyBytes>(py)?.data(py));
+    encoded.release_ref(py);
+    result
+}
+
+#[cfg(all(unix, feature="python27-sys"))]
+fn bytes_to_os_string(_py: Python, data: &[u8]) -> PyResult<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(OsString::from_vec(data.to_vec()))
+}
+
+#[cfg(all(windows, feature="python27-sys"))]
+fn bytes_to_os_string(py: Python, data: &[u8]) -> PyResult<OsString> {
+    use std::str;
+    use objects::exc;
+    match str::from_utf8(data) {
+        Ok(s) => Ok(OsString::from(s)),
+        Err(e) => Err(::err::PyErr::from_instance(py, exc::UnicodeDecodeError::new_utf8(py, data, e)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::{OsStr, OsString};
+    use std::path::{Path, PathBuf};
+    use python::{Python, PythonObject};
+    use conversion::{ToPyObject, RefFromPyObject};
+    use objects::PyDict;
+
+    #[test]
+    fn test_path_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let path = Path::new("/tmp/some dir/файл.txt");
+        let obj = path.to_py_object(py);
+        assert_eq!(obj.extract::<PathBuf>(py).unwrap(), path);
+        assert_eq!(obj.extract::<OsString>(py).unwrap(), path.as_os_str());
+        Path::with_extracted(py, &obj, |p| assert_eq!(p, path)).unwrap();
+        assert!(py.None().extract::<PathBuf>(py).is_err());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_path_to_str() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        d.set_item(py, "p", OsStr::new("a/b")).unwrap();
+        py.run("assert p == 'a/b'", None, Some(&d)).unwrap();
+        let obj = py.eval("b'x/y'", None, None).unwrap();
+        assert_eq!(obj.extract::<PathBuf>(py).unwrap(), Path::new("x/y"));
+    }
+
+    #[test]
+    #[cfg(all(feature="python3-sys", Py_3_6))]
+    fn test_path_like() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("__import__('pathlib').PurePosixPath('a', 'b.txt')", None, None).unwrap();
+        assert_eq!(obj.extract::<PathBuf>(py).unwrap(), Path::new("a/b.txt"));
+        OsStr::with_extracted(py, &obj, |s| assert_eq!(s, "a/b.txt")).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(unix, feature="python3-sys"))]
+    fn test_non_utf8_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let name = OsStr::from_bytes(b"caf\xe9");
+        let obj = name.to_py_object(py);
+        let d = PyDict::new(py);
+        d.set_item(py, "name", &obj).unwrap();
+        py.run("import os; assert os.fsencode(name) == b'caf\\xe9'", None, Some(&d)).unwrap();
+        assert_eq!(obj.extract::<OsString>(py).unwrap(), name);
+    }
+}
@@ -16,11 +16,12 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::mem;
+use std::{mem, ptr};
+use std::collections;
 use ffi;
 use python::{Python, PythonObject, ToPythonPointer, PyClone, PyDrop};
 use conversion::{FromPyObject, ToPyObject};
-use objects::{PyObject, PyList, PyTuple, PyIterator};
+use objects::{PyObject, PyList, PyTuple, PyIterator, exc};
 use ffi::Py_ssize_t;
 use err;
 use err::{PyErr, PyResult, result_from_owned_ptr, result_cast_from_owned_ptr};
@@ -247,6 +248,59 @@ impl <'source, T> FromPyObject<'source> for Vec<T>
     }
 }
 
+impl <'source, T> FromPyObject<'source> for Box<[T]>
+    where for<'a> T: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        obj.extract::<Vec<T>>(py).map(Vec::into_boxed_slice)
+    }
+}
+
+impl <'source, T> FromPyObject<'source> for collections::VecDeque<T>
+    where for<'a> T: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        extract_sequence(py, obj).map(collections::VecDeque::from)
+    }
+}
+
+impl <'source, T> FromPyObject<'source> for collections::LinkedList<T>
+    where for<'a> T: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        extract_sequence::<T>(py, obj).map(|v| v.into_iter().collect())
+    }
+}
+
+macro_rules! array_from_py_object {
+    ($($N:expr)+) => {
+        $(
+            /// Extracts a fixed-size array from a Python sequence.
+            /// Fails with `ValueError` if the sequence does not have exactly `N` items.
+            impl <'source, T> FromPyObject<'source> for [T; $N]
+                where for<'a> T: FromPyObject<'a>
+            {
+                fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+                    let mut v = obj.extract::<Vec<T>>(py)?;
+                    if v.len() != $N {
+                        let msg = format!("Expected sequence of length {}, but got sequence of length {}.", $N, v.len());
+                        return Err(PyErr::new::<exc::ValueError, _>(py, msg));
+                    }
+                    unsafe {
+                        // Move the items out of the vector without dropping them.
+                        let array = ptr::read(v.as_ptr() as *const [T; $N]);
+                        v.set_len(0);
+                        Ok(array)
+                    }
+                }
+            }
+        )+
+    }
+}
+
+array_from_py_object!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+
 fn extract_sequence<T>(py: Python, obj: &PyObject) -> PyResult<Vec<T>>
     where for<'a> T: FromPyObject<'a>
 {
@@ -516,4 +570,33 @@ mod test {
         let v: Vec<u8> = py.eval("bytearray(b'abc')", None, None).unwrap().extract(py).unwrap();
         assert!(v == b"abc");
     }
+
+
+    #[test]
+    fn test_extract_array() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = vec![1, 2, 3].to_py_object(py).into_object();
+        assert_eq!(obj.extract::<[i32; 3]>(py).unwrap(), [1, 2, 3]);
+        assert!(obj.extract::<[i32; 2]>(py).unwrap_err().matches(py, py.get_type::<::objects::exc::ValueError>()));
+        let obj = ("a", "b").to_py_object(py).into_object();
+        assert_eq!(obj.extract::<[String; 2]>(py).unwrap(), ["a".to_string(), "b".to_string()]);
+        assert_eq!([1u8, 2].to_py_object(py).len(py), 2);
+    }
+
+    #[test]
+    fn test_std_collections() {
+        use std::collections::{VecDeque, LinkedList};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let deque: VecDeque<i32> = vec![1, 2, 3].into_iter().collect();
+        let list = deque.to_py_object(py);
+        assert_eq!(list.into_object().extract::<VecDeque<i32>>(py).unwrap(), deque);
+        let linked: LinkedList<i32> = vec![4, 5].into_iter().collect();
+        let obj = linked.clone().into_py_object(py).into_object();
+        assert_eq!(obj.extract::<LinkedList<i32>>(py).unwrap(), linked);
+        let boxed = obj.extract::<Box<[i32]>>(py).unwrap();
+        assert_eq!(&*boxed, &[4, 5]);
+        assert_eq!(boxed.to_py_object(py).len(py), 2);
+    }
 }
@@ -17,9 +17,10 @@
 // DEALINGS IN THE SOFTWARE.
 
 use ffi;
-use python::{Python, PythonObject};
-use conversion::ToPyObject;
-use objects::PyObject;
+use python::{Python, PythonObject, PyDrop};
+use conversion::{ToPyObject, FromPyObject};
+use objectprotocol::ObjectProtocol;
+use objects::{PyObject, PyIterator};
 use err::{self, PyResult, PyErr};
 use std::{mem, collections, hash, cmp, ptr};
 
@@ -109,6 +110,51 @@ impl PySet {
     }
 }
 
+/// Represents a Python `frozenset`.
+pub struct PyFrozenSet(PyObject);
+
+pyobject_newtype!(PyFrozenSet, PyFrozenSet_Check, PyFrozenSet_Type);
+
+impl PyFrozenSet {
+    /// Creates a new frozenset from any iterable.
+    ///
+    /// Corresponds to `frozenset(iterable)` in Python.
+    pub fn new<I>(py: Python, iterable: I) -> PyResult<PyFrozenSet> where I: ToPyObject {
+        iterable.with_borrowed_ptr(py, |iterable| unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyFrozenSet_New(iterable))
+        })
+    }
+
+    /// Creates an empty frozenset.
+    ///
+    /// Corresponds to `frozenset()` in Python.
+    #[inline]
+    pub fn empty(py: Python) -> PyResult<PyFrozenSet> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyFrozenSet_New(ptr::null_mut()))
+        }
+    }
+
+    /// Return the number of items in the frozenset.
+    /// This is equivalent to Python `len(self)`.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PySet_Size(self.0.as_ptr()) as usize }
+    }
+
+    /// Determine if the frozenset contains the specified value.
+    /// This is equivalent to the Python expression `value in self`.
+    pub fn contains<V>(&self, py: Python, value: V) -> PyResult<bool> where V: ToPyObject {
+        value.with_borrowed_ptr(py, |key| unsafe {
+            match ffi::PySet_Contains(self.0.as_ptr(), key) {
+                1 => Ok(true),
+                0 => Ok(false),
+                _ => Err(PyErr::fetch(py))
+            }
+        })
+    }
+}
+
 impl <V, H> ToPyObject for collections::HashSet<V, H>
     where V: hash::Hash+cmp::Eq+ToPyObject,
           H: hash::BuildHasher
@@ -139,11 +185,132 @@ impl <V> ToPyObject for collections::BTreeSet<V>
 }
 
 
+macro_rules! set_algebra {
+    ($name: ident) => {
+        impl $name {
+            /// Returns an iterator over the items of the set.
+            ///
+            /// The iterator yields a `RuntimeError` if the set changes size during iteration.
+            #[inline]
+            pub fn iter<'p>(&self, py: Python<'p>) -> PyResult<PyIterator<'p>> {
+                self.0.iter(py)
+            }
+
+            /// Returns a new set of the same type with the items that are in `self` or in `other`, which can be any iterable.
+            /// This is equivalent to the Python expression `self.union(other)`.
+            pub fn union<O>(&self, py: Python, other: O) -> PyResult<$name> where O: ToPyObject {
+                Ok(self.0.call_method(py, "union", (other,), None)?.cast_into(py)?)
+            }
+
+            /// Returns a new set of the same type with the items that are in both `self` and `other`, which can be any iterable.
+            /// This is equivalent to the Python expression `self.intersection(other)`.
+            pub fn intersection<O>(&self, py: Python, other: O) -> PyResult<$name> where O: ToPyObject {
+                Ok(self.0.call_method(py, "intersection", (other,), None)?.cast_into(py)?)
+            }
+
+            /// Returns a new set of the same type with the items that are in `self` but not in `other`, which can be any iterable.
+            /// This is equivalent to the Python expression `self.difference(other)`.
+            pub fn difference<O>(&self, py: Python, other: O) -> PyResult<$name> where O: ToPyObject {
+                Ok(self.0.call_method(py, "difference", (other,), None)?.cast_into(py)?)
+            }
+
+            /// Returns a new set of the same type with the items that are in exactly one of `self` and `other`, which can be any iterable.
+            /// This is equivalent to the Python expression `self.symmetric_difference(other)`.
+            pub fn symmetric_difference<O>(&self, py: Python, other: O) -> PyResult<$name> where O: ToPyObject {
+                Ok(self.0.call_method(py, "symmetric_difference", (other,), None)?.cast_into(py)?)
+            }
+
+            /// Determines whether every item of `self` is in `other`, which can be any iterable.
+            /// This is equivalent to the Python expression `self.issubset(other)`.
+            pub fn is_subset<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+                self.0.call_method(py, "issubset", (other,), None)?.extract(py)
+            }
+
+            /// Determines whether every item of `other`, which can be any iterable, is in `self`.
+            /// This is equivalent to the Python expression `self.issuperset(other)`.
+            pub fn is_superset<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+                self.0.call_method(py, "issuperset", (other,), None)?.extract(py)
+            }
+
+            /// Determines whether `self` and `other`, which can be any iterable, have no items in common.
+            /// This is equivalent to the Python expression `self.isdisjoint(other)`.
+            pub fn is_disjoint<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+                self.0.call_method(py, "isdisjoint", (other,), None)?.extract(py)
+            }
+        }
+    }
+}
+
+set_algebra!(PySet);
+set_algebra!(PyFrozenSet);
+
+impl PySet {
+    /// Adds the items of `other`, which can be any iterable.
+    /// This is equivalent to the Python expression `self.update(other)`.
+    pub fn update<O>(&self, py: Python, other: O) -> PyResult<()> where O: ToPyObject {
+        self.0.call_method(py, "update", (other,), None)?.release_ref(py);
+        Ok(())
+    }
+
+    /// Removes the items that are not in `other`, which can be any iterable.
+    /// This is equivalent to the Python expression `self.intersection_update(other)`.
+    pub fn intersection_update<O>(&self, py: Python, other: O) -> PyResult<()> where O: ToPyObject {
+        self.0.call_method(py, "intersection_update", (other,), None)?.release_ref(py);
+        Ok(())
+    }
+
+    /// Removes the items that are in `other`, which can be any iterable.
+    /// This is equivalent to the Python expression `self.difference_update(other)`.
+    pub fn difference_update<O>(&self, py: Python, other: O) -> PyResult<()> where O: ToPyObject {
+        self.0.call_method(py, "difference_update", (other,), None)?.release_ref(py);
+        Ok(())
+    }
+
+    /// Replaces the set with the items that are in exactly one of `self` and `other`,
+    /// which can be any iterable.
+    /// This is equivalent to the Python expression `self.symmetric_difference_update(other)`.
+    pub fn symmetric_difference_update<O>(&self, py: Python, other: O) -> PyResult<()> where O: ToPyObject {
+        self.0.call_method(py, "symmetric_difference_update", (other,), None)?.release_ref(py);
+        Ok(())
+    }
+}
+
+/// Extracts a `HashSet` from a `set`, `frozenset` or any other iterable.
+impl <'source, V, H> FromPyObject<'source> for collections::HashSet<V, H>
+    where for<'a> V: FromPyObject<'a> + hash::Hash + cmp::Eq,
+          H: hash::BuildHasher + Default
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut set = collections::HashSet::default();
+        for item in obj.iter(py)? {
+            let item = item?;
+            set.insert(item.extract(py)?);
+            item.release_ref(py);
+        }
+        Ok(set)
+    }
+}
+
+/// Extracts a `BTreeSet` from a `set`, `frozenset` or any other iterable.
+impl <'source, V> FromPyObject<'source> for collections::BTreeSet<V>
+    where for<'a> V: FromPyObject<'a> + cmp::Ord
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut set = collections::BTreeSet::new();
+        for item in obj.iter(py)? {
+            let item = item?;
+            set.insert(item.extract(py)?);
+            item.release_ref(py);
+        }
+        Ok(set)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
-    use objects::PySet;
+    use objects::{PySet, PyFrozenSet};
     use std::collections::{HashSet, BTreeSet};
 
     #[test]
@@ -266,4 +433,92 @@ mod test {
         // original object not updated
         assert!(!v.contains(&31));
     }
+
+
+    #[test]
+    fn test_extract_sets() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("{'a', 'b'}", None, None).unwrap();
+        let set = obj.extract::<HashSet<String>>(py).unwrap();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("a") && set.contains("b"));
+        assert!(obj.extract::<HashSet<i32>>(py).is_err());
+
+        let obj = py.eval("frozenset([3, 1, 2])", None, None).unwrap();
+        let set = obj.extract::<BTreeSet<i32>>(py).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        let obj = py.eval("[1, 1, 2]", None, None).unwrap();
+        assert_eq!(obj.extract::<HashSet<i32>>(py).unwrap().len(), 2);
+        assert!(py.eval("1", None, None).unwrap().extract::<HashSet<i32>>(py).is_err());
+    }
+
+    #[test]
+    fn test_frozenset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PyFrozenSet::new(py, vec![1, 2, 2]).unwrap();
+        assert_eq!(set.len(py), 2);
+        assert!(set.contains(py, 1).unwrap());
+        assert!(!set.contains(py, 3).unwrap());
+        assert_eq!(PyFrozenSet::empty(py).unwrap().len(py), 0);
+        assert!(set.into_object().cast_into::<PySet>(py).is_err());
+        assert!(PySet::new(py, vec![1]).unwrap().into_object().cast_into::<PyFrozenSet>(py).is_err());
+    }
+
+
+    #[test]
+    fn test_set_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, vec![1, 2, 3]).unwrap();
+        let sum: i32 = set.iter(py).unwrap().map(|item| item.unwrap().extract::<i32>(py).unwrap()).sum();
+        assert_eq!(sum, 6);
+
+        let mut iter = set.iter(py).unwrap();
+        iter.next().unwrap().unwrap();
+        set.add(py, 4).unwrap();
+        assert!(iter.next().unwrap().is_err());
+
+        let frozen = PyFrozenSet::new(py, vec![5]).unwrap();
+        assert_eq!(frozen.iter(py).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let a = PySet::new(py, vec![1, 2, 3]).unwrap();
+        let b = vec![2, 3, 4];
+        let extract = |set: PySet| set.into_object().extract::<BTreeSet<i32>>(py).unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(extract(a.union(py, &b).unwrap()), [1, 2, 3, 4]);
+        assert_eq!(extract(a.intersection(py, &b).unwrap()), [2, 3]);
+        assert_eq!(extract(a.difference(py, &b).unwrap()), [1]);
+        assert_eq!(extract(a.symmetric_difference(py, &b).unwrap()), [1, 4]);
+        assert!(a.is_subset(py, vec![0, 1, 2, 3]).unwrap());
+        assert!(!a.is_subset(py, &b).unwrap());
+        assert!(a.is_superset(py, vec![1, 3]).unwrap());
+        assert!(a.is_disjoint(py, vec![7, 8]).unwrap());
+        assert!(a.union(py, 5).is_err());
+
+        let frozen = PyFrozenSet::new(py, vec![1, 2]).unwrap();
+        let union = frozen.union(py, &a).unwrap();
+        assert_eq!(union.len(py), 3);
+        assert!(union.into_object().cast_into::<PySet>(py).is_err());
+    }
+
+    #[test]
+    fn test_set_update() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, vec![1, 2, 3]).unwrap();
+        set.update(py, vec![4, 5]).unwrap();
+        assert_eq!(set.len(py), 5);
+        set.intersection_update(py, vec![1, 2, 4, 6]).unwrap();
+        assert_eq!(set.as_object().extract::<BTreeSet<i32>>(py).unwrap().into_iter().collect::<Vec<_>>(), [1, 2, 4]);
+        set.difference_update(py, vec![2]).unwrap();
+        set.symmetric_difference_update(py, vec![4, 9]).unwrap();
+        assert_eq!(set.as_object().extract::<BTreeSet<i32>>(py).unwrap().into_iter().collect::<Vec<_>>(), [1, 9]);
+        assert!(set.update(py, 1).is_err());
+    }
 }
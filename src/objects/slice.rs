@@ -0,0 +1,256 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ops;
+use ffi;
+use python::{Python, PythonObject, PyClone};
+use conversion::{FromPyObject, ToPyObject};
+use objects::{PyObject, exc};
+use err::{self, PyResult, PyErr};
+
+/// Represents a Python `slice` object.
+pub struct PySlice(PyObject);
+
+pyobject_newtype!(PySlice, PySlice_Check, PySlice_Type);
+
+/// The indices of a slice, resolved for a sequence of a given length.
+///
+/// Returned by `PySlice::indices()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PySliceIndices {
+    /// Index of the first element.
+    pub start: isize,
+    /// End index; may be `-1` for slices with a negative step that include the first element.
+    pub stop: isize,
+    /// Step between elements; never zero.
+    pub step: isize,
+    /// Number of elements selected by the slice.
+    pub slicelength: usize,
+}
+
+impl PySliceIndices {
+    /// Gets the index of the `i`-th element selected by the slice.
+    #[inline]
+    pub fn index(&self, i: usize) -> isize {
+        self.start + (i as isize) * self.step
+    }
+}
+
+impl PySlice {
+    /// Creates a new slice object.
+    ///
+    /// Corresponds to `slice(start, stop, step)` in Python; `None` parameters are passed as `None`.
+    pub fn new(py: Python, start: Option<isize>, stop: Option<isize>, step: Option<isize>) -> PySlice {
+        new_slice(py, start, stop, step)
+    }
+
+    /// Gets the `start` attribute of the slice.
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn start(&self, py: Python) -> PyObject {
+        unsafe { PyObject::from_borrowed_ptr(py, (*(self.0.as_ptr() as *mut ffi::PySliceObject)).start) }
+    }
+
+    /// Gets the `start` attribute of the slice.
+    #[cfg(Py_LIMITED_API)]
+    pub fn start(&self, py: Python) -> PyObject {
+        unsafe { ::err::from_owned_ptr_or_panic(py, ffi::PyObject_GetAttrString(self.0.as_ptr(), "start\0".as_ptr() as *const _)) }
+    }
+
+    /// Gets the `stop` attribute of the slice.
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn stop(&self, py: Python) -> PyObject {
+        unsafe { PyObject::from_borrowed_ptr(py, (*(self.0.as_ptr() as *mut ffi::PySliceObject)).stop) }
+    }
+
+    /// Gets the `stop` attribute of the slice.
+    #[cfg(Py_LIMITED_API)]
+    pub fn stop(&self, py: Python) -> PyObject {
+        unsafe { ::err::from_owned_ptr_or_panic(py, ffi::PyObject_GetAttrString(self.0.as_ptr(), "stop\0".as_ptr() as *const _)) }
+    }
+
+    /// Gets the `step` attribute of the slice.
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn step(&self, py: Python) -> PyObject {
+        unsafe { PyObject::from_borrowed_ptr(py, (*(self.0.as_ptr() as *mut ffi::PySliceObject)).step) }
+    }
+
+    /// Gets the `step` attribute of the slice.
+    #[cfg(Py_LIMITED_API)]
+    pub fn step(&self, py: Python) -> PyObject {
+        unsafe { ::err::from_owned_ptr_or_panic(py, ffi::PyObject_GetAttrString(self.0.as_ptr(), "step\0".as_ptr() as *const _)) }
+    }
+
+    /// Resolves the slice for a sequence of length `length`.
+    ///
+    /// Out-of-bounds indices are clipped, like for Python lists.
+    /// Fails with `ValueError` if the step is zero.
+    pub fn indices(&self, py: Python, length: usize) -> PyResult<PySliceIndices> {
+        let mut start: ffi::Py_ssize_t = 0;
+        let mut stop: ffi::Py_ssize_t = 0;
+        let mut step: ffi::Py_ssize_t = 0;
+        let mut slicelength: ffi::Py_ssize_t = 0;
+        unsafe {
+            err::error_on_minusone(py, ffi::PySlice_GetIndicesEx(
+                self.0.as_ptr(), length as ffi::Py_ssize_t,
+                &mut start, &mut stop, &mut step, &mut slicelength))?;
+        }
+        Ok(PySliceIndices {
+            start: start as isize,
+            stop: stop as isize,
+            step: step as isize,
+            slicelength: slicelength as usize,
+        })
+    }
+}
+
+fn new_slice(py: Python, start: Option<isize>, stop: Option<isize>, step: Option<isize>) -> PySlice {
+    let start = start.to_py_object(py);
+    let stop = stop.to_py_object(py);
+    let step = step.to_py_object(py);
+    unsafe {
+        err::cast_from_owned_ptr_or_panic(py,
+            ffi::PySlice_New(start.as_ptr(), stop.as_ptr(), step.as_ptr()))
+    }
+}
+
+/// Key of a `__getitem__`, `__setitem__` or `__delitem__` call on a sequence:
+/// either an integer index or a slice.
+///
+/// Extracting `SliceOrIndex` accepts slice objects and any object supported by
+/// `isize` extraction; other objects fail with `TypeError`.
+pub enum SliceOrIndex {
+    /// An integer index; may be negative.
+    Index(isize),
+    /// A slice object.
+    Slice(PySlice),
+}
+
+impl <'source> FromPyObject<'source> for SliceOrIndex {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<SliceOrIndex> {
+        if let Ok(slice) = obj.cast_as::<PySlice>(py) {
+            return Ok(SliceOrIndex::Slice(slice.clone_ref(py)));
+        }
+        match obj.extract::<isize>(py) {
+            Ok(index) => Ok(SliceOrIndex::Index(index)),
+            Err(mut e) => {
+                if e.matches(py, py.get_type::<exc::TypeError>()) {
+                    e = PyErr::new::<exc::TypeError, _>(py,
+                        "indices must be integers or slices");
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Converts `start..stop` to `slice(start, stop)`.
+impl ToPyObject for ops::Range<isize> {
+    type ObjectType = PySlice;
+
+    fn to_py_object(&self, py: Python) -> PySlice {
+        new_slice(py, Some(self.start), Some(self.end), None)
+    }
+}
+
+/// Converts `start..` to `slice(start, None)`.
+impl ToPyObject for ops::RangeFrom<isize> {
+    type ObjectType = PySlice;
+
+    fn to_py_object(&self, py: Python) -> PySlice {
+        new_slice(py, Some(self.start), None, None)
+    }
+}
+
+/// Converts `..stop` to `slice(None, stop)`.
+impl ToPyObject for ops::RangeTo<isize> {
+    type ObjectType = PySlice;
+
+    fn to_py_object(&self, py: Python) -> PySlice {
+        new_slice(py, None, Some(self.end), None)
+    }
+}
+
+/// Converts `..` to `slice(None)`.
+impl ToPyObject for ops::RangeFull {
+    type ObjectType = PySlice;
+
+    fn to_py_object(&self, py: Python) -> PySlice {
+        new_slice(py, None, None, None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::{PyList, exc};
+    use super::{PySlice, PySliceIndices, SliceOrIndex};
+
+    #[test]
+    fn test_indices() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let slice = PySlice::new(py, Some(1), Some(10), Some(2));
+        assert_eq!(slice.indices(py, 5).unwrap(),
+            PySliceIndices { start: 1, stop: 5, step: 2, slicelength: 2 });
+        let slice = PySlice::new(py, None, None, Some(-1));
+        let indices = slice.indices(py, 3).unwrap();
+        assert_eq!(indices, PySliceIndices { start: 2, stop: -1, step: -1, slicelength: 3 });
+        assert_eq!(indices.index(2), 0);
+        let slice = PySlice::new(py, None, None, Some(0));
+        assert!(slice.indices(py, 3).unwrap_err().matches(py, py.get_type::<exc::ValueError>()));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let slice = PySlice::new(py, Some(1), None, Some(2));
+        assert_eq!(slice.start(py).extract::<isize>(py).unwrap(), 1);
+        assert!(slice.stop(py) == py.None());
+        assert_eq!(slice.step(py).extract::<isize>(py).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::new(py, &[0i32, 1, 2, 3].iter().map(|i| i.to_py_object(py).into_object()).collect::<Vec<_>>());
+        let get = |key: PySlice| list.as_object().get_item(py, key).unwrap().extract::<Vec<i32>>(py).unwrap();
+        assert_eq!(get((1..3).to_py_object(py)), vec![1, 2]);
+        assert_eq!(get((2..).to_py_object(py)), vec![2, 3]);
+        assert_eq!(get((..-1).to_py_object(py)), vec![0, 1, 2]);
+        assert_eq!(get((..).to_py_object(py)), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_slice_or_index() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        match (1..2).to_py_object(py).into_object().extract::<SliceOrIndex>(py).unwrap() {
+            SliceOrIndex::Slice(slice) => assert_eq!(slice.indices(py, 5).unwrap().slicelength, 1),
+            SliceOrIndex::Index(_) => panic!()
+        }
+        match (-3isize).to_py_object(py).into_object().extract::<SliceOrIndex>(py).unwrap() {
+            SliceOrIndex::Index(index) => assert_eq!(index, -3),
+            SliceOrIndex::Slice(_) => panic!()
+        }
+        assert!("abc".to_py_object(py).into_object().extract::<SliceOrIndex>(py).is_err());
+    }
+}
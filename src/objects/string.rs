@@ -41,6 +41,11 @@ pub struct PyBytes(PyObject);
 
 pyobject_newtype!(PyBytes, PyBytes_Check, PyBytes_Type);
 
+/// Represents a Python `bytearray`.
+pub struct PyByteArray(PyObject);
+
+pyobject_newtype!(PyByteArray, PyByteArray_Check, PyByteArray_Type);
+
 /// Represents a Python unicode string.
 /// Corresponds to `unicode` in Python 2, and `str` in Python 3.
 #[cfg(feature="python27-sys")]
@@ -329,6 +334,94 @@ impl PyBytes {
     }
 }
 
+impl PyByteArray {
+    /// Creates a new Python `bytearray` object.
+    /// The bytearray is initialized by copying the data from the `&[u8]`.
+    ///
+    /// Panics if out of memory.
+    pub fn new(py: Python, s: &[u8]) -> PyByteArray {
+        let ptr = s.as_ptr() as *const c_char;
+        let len = s.len() as ffi::Py_ssize_t;
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py,
+                ffi::PyByteArray_FromStringAndSize(ptr, len))
+        }
+    }
+
+    /// Creates a new Python `bytearray` object from any object implementing the buffer protocol.
+    ///
+    /// Corresponds to `bytearray(obj)` in Python.
+    pub fn from_object(py: Python, obj: &PyObject) -> PyResult<PyByteArray> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyByteArray_FromObject(obj.as_ptr()))
+        }
+    }
+
+    /// Gets the length of the bytearray.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PyByteArray_Size(self.as_ptr()) as usize }
+    }
+
+    /// Checks if the bytearray is empty.
+    #[inline]
+    pub fn is_empty(&self, py: Python) -> bool {
+        self.len(py) == 0
+    }
+
+    /// Resizes the bytearray to `len` bytes.
+    /// When growing, the new bytes are set to zero.
+    ///
+    /// Fails with `OverflowError` if `len` does not fit into `isize`,
+    /// and with `BufferError` if the bytearray is currently exported through the buffer protocol.
+    pub fn resize(&self, py: Python, len: usize) -> PyResult<()> {
+        if len > std::isize::MAX as usize {
+            return Err(PyErr::new::<exc::OverflowError, _>(py, "bytearray size is too large"));
+        }
+        let old_len = self.len(py);
+        unsafe {
+            err::error_on_minusone(py, ffi::PyByteArray_Resize(self.as_ptr(), len as ffi::Py_ssize_t))?;
+            if len > old_len {
+                let buffer = ffi::PyByteArray_AsString(self.as_ptr()) as *mut u8;
+                std::ptr::write_bytes(buffer.offset(old_len as isize), 0, len - old_len);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the contents of the bytearray into a `Vec<u8>`.
+    pub fn to_vec(&self, py: Python) -> Vec<u8> {
+        unsafe { self.data(py) }.to_vec()
+    }
+
+    /// Gets the bytearray contents as byte slice.
+    ///
+    /// # Safety
+    /// The bytearray is mutable, and may be shared with Python code.
+    /// The caller must ensure that the bytearray is not resized or modified
+    /// (e.g. by running Python code) while the slice is in use.
+    pub unsafe fn data(&self, _py: Python) -> &[u8] {
+        let buffer = ffi::PyByteArray_AsString(self.as_ptr()) as *const u8;
+        let length = ffi::PyByteArray_Size(self.as_ptr()) as usize;
+        std::slice::from_raw_parts(buffer, length)
+    }
+
+    /// Gets the bytearray contents as mutable byte slice.
+    ///
+    /// # Safety
+    /// Taking `&mut self` does not make the access exclusive: other handles to the
+    /// same bytearray can exist (e.g. obtained via `clone_ref()`, or any `PyObject`
+    /// referring to it), and Python code may hold references as well.
+    /// The caller must ensure that no other reference reads, writes or resizes the
+    /// bytearray while the slice is in use; this includes running Python code and
+    /// calling `data()` or `data_mut()` through any handle.
+    pub unsafe fn data_mut(&mut self, _py: Python) -> &mut [u8] {
+        let buffer = ffi::PyByteArray_AsString(self.0.as_ptr()) as *mut u8;
+        let length = ffi::PyByteArray_Size(self.0.as_ptr()) as usize;
+        std::slice::from_raw_parts_mut(buffer, length)
+    }
+}
+
 #[cfg(feature="python27-sys")]
 impl PyUnicode {
     /// Creates a new Python unicode string object.
@@ -417,6 +510,17 @@ impl ToPyObject for String {
     }
 }
 
+/// Converts Rust `char` to a Python string of length 1.
+impl ToPyObject for char {
+    type ObjectType = PyString;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyString {
+        let mut buf = [0u8; 4];
+        PyString::new(py, self.encode_utf8(&mut buf))
+    }
+}
+
 /// Allows extracting strings from Python objects.
 /// Accepts Python `str` and `unicode` objects.
 /// In Python 2.7, `str` is expected to be UTF-8 encoded.
@@ -435,6 +539,22 @@ impl <'source> FromPyObject<'source> for String {
     }
 }
 
+/// Allows extracting a `char` from a Python string of length 1.
+/// Fails with `ValueError` for strings of any other length.
+impl <'source> FromPyObject<'source> for char {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let s = obj.extract::<Cow<str>>(py)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => {
+                let msg = format!("expected a string of length 1, but got a string of length {}", s.chars().count());
+                Err(PyErr::new::<exc::ValueError, _>(py, msg))
+            }
+        }
+    }
+}
+
 /// Allows extracting byte arrays from Python objects.
 /// For Python `bytes`, returns a reference to the existing immutable string data.
 /// For other types, converts to an owned `Vec<u8>`.
@@ -442,6 +562,8 @@ impl <'source> FromPyObject<'source> for Cow<'source, [u8]> {
     fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
         if let Ok(bytes) = obj.cast_as::<PyBytes>(py) {
             Ok(Cow::Borrowed(bytes.data(py)))
+        } else if let Ok(bytearray) = obj.cast_as::<PyByteArray>(py) {
+            Ok(Cow::Owned(bytearray.to_vec(py)))
         } else {
             obj.extract::<Vec<u8>>(py).map(Cow::Owned)
         }
@@ -468,8 +590,12 @@ impl RefFromPyObject for [u8] {
 
 #[cfg(test)]
 mod test {
+    use std::borrow::Cow;
     use python::{Python, PythonObject};
     use conversion::{ToPyObject, RefFromPyObject};
+    use objectprotocol::ObjectProtocol;
+    use objects::exc;
+    use super::{PyBytes, PyByteArray};
 
     #[test]
     fn test_non_bmp() {
@@ -518,4 +644,59 @@ mod test {
         let v = py_bytes.extract::<Vec<u8>>(py).unwrap();
         assert_eq!(b"Hello", &v[..]);
     }
+
+    #[test]
+    fn test_bytearray() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut bytearray = PyByteArray::new(py, b"Hello");
+        assert_eq!(bytearray.len(py), 5);
+        assert_eq!(unsafe { bytearray.data(py) }, b"Hello");
+        unsafe { bytearray.data_mut(py)[0] = b'J' };
+        bytearray.resize(py, 4).unwrap();
+        assert_eq!(bytearray.to_vec(py), b"Jell");
+        let obj = bytearray.into_object();
+        assert_eq!(py.eval("bytearray(b'Jell')", None, None).unwrap().compare(py, &obj).unwrap(),
+            ::std::cmp::Ordering::Equal);
+        assert_eq!(&*obj.extract::<Cow<[u8]>>(py).unwrap(), b"Jell");
+    }
+
+    #[test]
+    fn test_bytearray_resize() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytearray = PyByteArray::new(py, b"abc");
+        bytearray.resize(py, 1).unwrap();
+        bytearray.resize(py, 100).unwrap();
+        assert_eq!(bytearray.to_vec(py)[..3], [b'a', 0, 0]);
+        assert!(bytearray.to_vec(py)[1..].iter().all(|&b| b == 0));
+        let err = bytearray.resize(py, ::std::usize::MAX).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::OverflowError>()));
+        assert_eq!(bytearray.len(py), 100);
+    }
+
+    #[test]
+    fn test_bytearray_from_object() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = PyBytes::new(py, b"abc");
+        let bytearray = PyByteArray::from_object(py, bytes.as_object()).unwrap();
+        assert_eq!(bytearray.to_vec(py), b"abc");
+        assert!(!bytearray.is_empty(py));
+        assert!(PyByteArray::from_object(py, &py.None()).is_err());
+    }
+
+
+    #[test]
+    fn test_char() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for &c in &['a', 'é', '\u{1F600}'] {
+            let obj = c.to_py_object(py).into_object();
+            assert_eq!(obj.extract::<char>(py).unwrap(), c);
+        }
+        assert!("ab".to_py_object(py).into_object().extract::<char>(py).is_err());
+        assert!("".to_py_object(py).into_object().extract::<char>(py).is_err());
+        assert!(py.None().extract::<char>(py).is_err());
+    }
 }
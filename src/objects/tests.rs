@@ -19,6 +19,25 @@
 use {Python, PyDict, ToPyObject, PyInt};
 use std::collections::{BTreeMap, HashMap};
 
+/// Creates a `UserDict` (a mapping that isn't a `dict` subclass) with the contents of `dict`.
+#[cfg(test)]
+pub fn user_dict(py: Python, dict: &PyDict) -> ::PyObject {
+    #[cfg(feature="python27-sys")]
+    let module = "UserDict";
+    #[cfg(feature="python3-sys")]
+    let module = "collections";
+    py.import(module).unwrap().call(py, "UserDict", (dict,), None).unwrap()
+}
+
+/// Extracts a list, sorted so that tests don't depend on the order of
+/// Python 2 dicts (which don't preserve insertion order).
+#[cfg(test)]
+pub fn extract_sorted<T>(py: Python, obj: &::PyObject) -> Vec<T> where T: Ord, for<'s> T: ::FromPyObject<'s> {
+    let mut v: Vec<T> = obj.extract(py).unwrap();
+    v.sort();
+    v
+}
+
 // TODO: move these tests into the dict module
 #[test]
 fn test_hashmap_to_python() {
@@ -0,0 +1,205 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ptr;
+use ffi;
+use python::{Python, PythonObject};
+use objects::PyObject;
+use function::py_fn_closure;
+use err::{self, PyResult};
+
+/// Represents a Python weak reference (`weakref.ref`).
+pub struct PyWeakRef(PyObject);
+
+pyobject_newtype!(PyWeakRef, PyWeakref_CheckRef);
+
+/// Represents a Python weak reference proxy (`weakref.proxy`).
+pub struct PyWeakProxy(PyObject);
+
+pyobject_newtype!(PyWeakProxy, PyWeakref_CheckProxy);
+
+impl PyWeakRef {
+    /// Creates a new weak reference to `obj`.
+    ///
+    /// Fails with `TypeError` if `obj` cannot be weakly referenced.
+    pub fn new(py: Python, obj: &PyObject) -> PyResult<PyWeakRef> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyWeakref_NewRef(obj.as_ptr(), ptr::null_mut()))
+        }
+    }
+
+    /// Creates a new weak reference to `obj`.
+    ///
+    /// `callback` is called with the weak reference when `obj` is about to be finalized.
+    /// It is not called if the weak reference itself is deallocated first.
+    pub fn new_with_callback<F>(py: Python, obj: &PyObject, callback: F) -> PyResult<PyWeakRef>
+        where F: Fn(Python, PyWeakRef) + Send + 'static
+    {
+        let callback = weakref_callback(py, callback);
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyWeakref_NewRef(obj.as_ptr(), callback.as_ptr()))
+        }
+    }
+
+    /// Gets a strong reference to the referenced object,
+    /// or `None` if the object was already collected.
+    #[inline]
+    pub fn upgrade(&self, py: Python) -> Option<PyObject> {
+        get_object(py, &self.0)
+    }
+
+    /// Checks whether the referenced object is still alive.
+    #[inline]
+    pub fn is_alive(&self, py: Python) -> bool {
+        is_alive(py, &self.0)
+    }
+}
+
+impl PyWeakProxy {
+    /// Creates a new weak reference proxy to `obj`.
+    ///
+    /// Fails with `TypeError` if `obj` cannot be weakly referenced.
+    pub fn new(py: Python, obj: &PyObject) -> PyResult<PyWeakProxy> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyWeakref_NewProxy(obj.as_ptr(), ptr::null_mut()))
+        }
+    }
+
+    /// Creates a new weak reference proxy to `obj`.
+    ///
+    /// `callback` is called with the proxy when `obj` is about to be finalized.
+    /// It is not called if the proxy itself is deallocated first.
+    pub fn new_with_callback<F>(py: Python, obj: &PyObject, callback: F) -> PyResult<PyWeakProxy>
+        where F: Fn(Python, PyWeakProxy) + Send + 'static
+    {
+        let callback = weakref_callback(py, callback);
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyWeakref_NewProxy(obj.as_ptr(), callback.as_ptr()))
+        }
+    }
+
+    /// Gets a strong reference to the referenced object,
+    /// or `None` if the object was already collected.
+    #[inline]
+    pub fn upgrade(&self, py: Python) -> Option<PyObject> {
+        get_object(py, &self.0)
+    }
+
+    /// Checks whether the referenced object is still alive.
+    #[inline]
+    pub fn is_alive(&self, py: Python) -> bool {
+        is_alive(py, &self.0)
+    }
+}
+
+fn get_object(py: Python, weakref: &PyObject) -> Option<PyObject> {
+    unsafe {
+        // PyWeakref_GetObject returns a borrowed reference, or None if the object is dead.
+        let obj = ffi::PyWeakref_GetObject(weakref.as_ptr());
+        if obj == ffi::Py_None() {
+            None
+        } else {
+            Some(PyObject::from_borrowed_ptr(py, obj))
+        }
+    }
+}
+
+fn is_alive(_py: Python, weakref: &PyObject) -> bool {
+    unsafe { ffi::PyWeakref_GetObject(weakref.as_ptr()) != ffi::Py_None() }
+}
+
+fn weakref_callback<T, F>(py: Python, callback: F) -> PyObject
+    where T: PythonObject, F: Fn(Python, T) + Send + 'static
+{
+    py_fn_closure(py, "weakref_callback", move |py, args, _kwargs| {
+        let weakref = unsafe { args.get_item(py, 0).unchecked_cast_into::<T>() };
+        callback(py, weakref);
+        Ok(py.None())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use python::{Python, PythonObject};
+    use objects::{PyObject, exc};
+    use objectprotocol::ObjectProtocol;
+    use super::{PyWeakRef, PyWeakProxy};
+
+    fn weakrefable_object(py: Python) -> PyObject {
+        py.eval("type('A', (object,), {})()", None, None).unwrap()
+    }
+
+    #[test]
+    fn test_weakref() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = weakrefable_object(py);
+        let weakref = PyWeakRef::new(py, &obj).unwrap();
+        assert!(weakref.is_alive(py));
+        assert!(weakref.upgrade(py).unwrap() == obj);
+        drop(obj);
+        assert!(!weakref.is_alive(py));
+        assert!(weakref.upgrade(py).is_none());
+    }
+
+    #[test]
+    fn test_not_weakrefable() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = PyWeakRef::new(py, &py.None()).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+    }
+
+    #[test]
+    fn test_weakref_callback() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = weakrefable_object(py);
+        let called = Arc::new(AtomicBool::new(false));
+        let called2 = called.clone();
+        let weakref = PyWeakRef::new_with_callback(py, &obj, move |py, weakref| {
+            assert!(!weakref.is_alive(py));
+            called2.store(true, Ordering::Relaxed);
+        }).unwrap();
+        assert!(!called.load(Ordering::Relaxed));
+        drop(obj);
+        assert!(called.load(Ordering::Relaxed));
+        drop(weakref);
+    }
+
+    #[test]
+    fn test_weakproxy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = weakrefable_object(py);
+        obj.setattr(py, "x", 42).unwrap();
+        let called = Arc::new(AtomicBool::new(false));
+        let called2 = called.clone();
+        let proxy = PyWeakProxy::new_with_callback(py, &obj, move |_py, _proxy| {
+            called2.store(true, Ordering::Relaxed);
+        }).unwrap();
+        assert_eq!(proxy.as_object().getattr(py, "x").unwrap().extract::<i32>(py).unwrap(), 42);
+        assert!(proxy.upgrade(py).unwrap() == obj);
+        drop(obj);
+        assert!(called.load(Ordering::Relaxed));
+        assert!(!proxy.is_alive(py));
+        assert!(proxy.as_object().getattr(py, "x").is_err());
+    }
+}
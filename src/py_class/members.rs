@@ -68,28 +68,16 @@ macro_rules! py_class_init_members {
 #[doc(hidden)]
 macro_rules! py_class_instance_method {
     ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
-        unsafe extern "C" fn wrap_instance_method(
-            slf: *mut $crate::_detail::ffi::PyObject,
-            args: *mut $crate::_detail::ffi::PyObject,
-            kwargs: *mut $crate::_detail::ffi::PyObject)
-        -> *mut $crate::_detail::ffi::PyObject
-        {
-            const LOCATION: &'static str = _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f), "()");
-            $crate::_detail::handle_callback(
-                LOCATION, $crate::_detail::PyObjectCallbackConverter,
-                |py| {
-                    py_argparse_raw!(py, Some(LOCATION), args, kwargs,
-                        [ $( { $pname : $ptype = $detail } )* ]
-                        {
-                            let slf = $crate::PyObject::from_borrowed_ptr(py, slf).unchecked_cast_into::<$class>();
-                            let ret = slf.$f(py $(, $pname )* );
-                            $crate::PyDrop::release_ref(slf, py);
-                            ret
-                        })
-                })
-        }
         unsafe {
-            let method_def = py_method_def!(_cpython__py_class__members__stringify!($f), 0, wrap_instance_method);
+            let method_def = py_method_def_for_params!(_cpython__py_class__members__stringify!($f), 0,
+                _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f)),
+                |py, slf| [ $( { $pname : $ptype = $detail } )* ]
+                {
+                    let slf = $crate::PyObject::from_borrowed_ptr(py, slf).unchecked_cast_into::<$class>();
+                    let ret = slf.$f(py $(, $pname )* );
+                    $crate::PyDrop::release_ref(slf, py);
+                    ret
+                });
             $crate::py_class::members::create_instance_method_descriptor::<$class>(method_def)
         }
     }}
@@ -111,34 +99,21 @@ impl <T> TypeMember<T> for InstanceMethodDescriptor<T> where T: PythonObject {
     }
 }
 
-#[macro_export]
+#[macro_export(local_inner_macros)]
 #[doc(hidden)]
 macro_rules! py_class_class_method {
     ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
-        unsafe extern "C" fn wrap_class_method(
-            cls: *mut $crate::_detail::ffi::PyObject,
-            args: *mut $crate::_detail::ffi::PyObject,
-            kwargs: *mut $crate::_detail::ffi::PyObject)
-        -> *mut $crate::_detail::ffi::PyObject
-        {
-            const LOCATION: &'static str = _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f), "()");
-            $crate::_detail::handle_callback(
-                LOCATION, $crate::_detail::PyObjectCallbackConverter,
-                |py| {
-                    py_argparse_raw!(py, Some(LOCATION), args, kwargs,
-                        [ $( { $pname : $ptype = $detail } )* ]
-                        {
-                            let cls = $crate::PyObject::from_borrowed_ptr(py, cls).unchecked_cast_into::<$crate::PyType>();
-                            let ret = $class::$f(&cls, py $(, $pname )* );
-                            $crate::PyDrop::release_ref(cls, py);
-                            ret
-                        })
-                })
-        }
         unsafe {
-            let method_def = py_method_def!(_cpython__py_class__members__stringify!($f),
+            let method_def = py_method_def_for_params!(_cpython__py_class__members__stringify!($f),
                 $crate::_detail::ffi::METH_CLASS,
-                wrap_class_method);
+                _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f)),
+                |py, cls| [ $( { $pname : $ptype = $detail } )* ]
+                {
+                    let cls = $crate::PyObject::from_borrowed_ptr(py, cls).unchecked_cast_into::<$crate::PyType>();
+                    let ret = $class::$f(&cls, py $(, $pname )* );
+                    $crate::PyDrop::release_ref(cls, py);
+                    ret
+                });
             $crate::py_class::members::create_class_method_descriptor(method_def)
         }
     }}
@@ -161,31 +136,18 @@ impl <T> TypeMember<T> for ClassMethodDescriptor where T: PythonObject {
 }
 
 
-#[macro_export]
+#[macro_export(local_inner_macros)]
 #[doc(hidden)]
 macro_rules! py_class_static_method {
     ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
-        unsafe extern "C" fn wrap_static_method(
-            _slf: *mut $crate::_detail::ffi::PyObject,
-            args: *mut $crate::_detail::ffi::PyObject,
-            kwargs: *mut $crate::_detail::ffi::PyObject)
-        -> *mut $crate::_detail::ffi::PyObject
-        {
-            const LOCATION: &'static str = _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f), "()");
-            $crate::_detail::handle_callback(
-                LOCATION, $crate::_detail::PyObjectCallbackConverter,
-                |py| {
-                    py_argparse_raw!(py, Some(LOCATION), args, kwargs,
-                        [ $( { $pname : $ptype = $detail } )* ]
-                        {
-                            $class::$f(py $(, $pname )* )
-                        })
-                })
-        }
         unsafe {
-            let method_def = py_method_def!(_cpython__py_class__members__stringify!($f),
+            let method_def = py_method_def_for_params!(_cpython__py_class__members__stringify!($f),
                 $crate::_detail::ffi::METH_STATIC,
-                wrap_static_method);
+                _cpython__py_class__members__concat!(_cpython__py_class__members__stringify!($class), ".", _cpython__py_class__members__stringify!($f)),
+                |py, _slf| [ $( { $pname : $ptype = $detail } )* ]
+                {
+                    $class::$f(py $(, $pname )* )
+                });
             $crate::_detail::py_fn_impl($py, method_def)
         }
     }}
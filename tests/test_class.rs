@@ -3,7 +3,7 @@
 #[macro_use] extern crate cpython;
 
 use cpython::*;
-use std::{mem, isize, iter};
+use std::{cmp, mem, isize, iter};
 use std::cell::{Cell, RefCell};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -33,6 +33,17 @@ macro_rules! py_expect_exception {
     }}
 }
 
+macro_rules! py_expect_type_error {
+    ($py:expr, $val:ident, $code:expr, $msg:expr) => {{
+        let d = PyDict::new($py);
+        d.set_item($py, stringify!($val), &$val).unwrap();
+        let mut err = $py.run($code, None, Some(&d)).unwrap_err();
+        if !err.matches($py, $py.get_type::<exc::TypeError>()) {
+            panic!(format!("Expected TypeError but got {:?}", err))
+        }
+        assert_eq!(err.instance($py).str($py).unwrap().to_string_lossy($py), $msg);
+    }}
+}
 
 py_class!(class EmptyClass |py| { });
 
@@ -181,6 +192,29 @@ fn instance_method_with_args() {
     py.run("assert obj.method(multiplier=6) == 42", None, Some(&d)).unwrap();
 }
 
+py_class!(class InstanceMethodWithKeywordOnlyArgs |py| {
+    data member: i32;
+
+    def method(&self, multiplier: i32, /, *, offset: i32 = 0) -> PyResult<i32> {
+        Ok(*self.member(py) * multiplier + offset)
+    }
+});
+
+#[test]
+fn instance_method_with_keyword_only_args() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let obj = InstanceMethodWithKeywordOnlyArgs::create_instance(py, 7).unwrap();
+    assert!(obj.method(py, 6, 0).unwrap() == 42);
+    let d = PyDict::new(py);
+    d.set_item(py, "obj", obj).unwrap();
+    py.run("assert obj.method(3) == 21", None, Some(&d)).unwrap();
+    py.run("assert obj.method(3, offset=1) == 22", None, Some(&d)).unwrap();
+    py.run("try:\n  obj.method(multiplier=3)\nexcept TypeError: pass\nelse: assert False", None, Some(&d)).unwrap();
+    py.run("try:\n  obj.method(3, 1)\nexcept TypeError: pass\nelse: assert False", None, Some(&d)).unwrap();
+}
+
 py_class!(class ClassMethod |py| {
     def __new__(cls) -> PyResult<ClassMethod> {
         ClassMethod::create_instance(py)
@@ -466,6 +500,134 @@ fn sequence() {
     py_assert!(py, c, "c['abc'] == 'abc'");
 }
 
+py_class!(class SliceableSequence |py| {
+    data items: RefCell<Vec<i32>>;
+
+    def __len__(&self) -> PyResult<usize> {
+        Ok(self.items(py).borrow().len())
+    }
+
+    def __getitem__(&self, key: SliceOrIndex) -> PyResult<PyObject> {
+        let items = self.items(py).borrow();
+        match key {
+            SliceOrIndex::Index(index) => {
+                let index = normalize_index(py, index, items.len())?;
+                Ok(items[index].to_py_object(py).into_object())
+            }
+            SliceOrIndex::Slice(slice) => {
+                let indices = slice.indices(py, items.len())?;
+                let result: Vec<i32> = (0..indices.slicelength)
+                    .map(|i| items[indices.index(i) as usize])
+                    .collect();
+                Ok(result.to_py_object(py).into_object())
+            }
+        }
+    }
+
+    def __setitem__(&self, key: SliceOrIndex, value: PyObject) -> PyResult<()> {
+        let mut items = self.items(py).borrow_mut();
+        match key {
+            SliceOrIndex::Index(index) => {
+                let index = normalize_index(py, index, items.len())?;
+                items[index] = value.extract(py)?;
+            }
+            SliceOrIndex::Slice(slice) => {
+                let indices = slice.indices(py, items.len())?;
+                if indices.step != 1 {
+                    return Err(PyErr::new::<exc::ValueError, _>(py, "extended slices are not supported"));
+                }
+                let start = indices.start as usize;
+                let stop = cmp::max(indices.start, indices.stop) as usize;
+                let values: Vec<i32> = value.extract(py)?;
+                let tail = items.split_off(stop);
+                items.truncate(start);
+                items.extend(values);
+                items.extend(tail);
+            }
+        }
+        Ok(())
+    }
+
+    def __delitem__(&self, key: SliceOrIndex) -> PyResult<()> {
+        let mut items = self.items(py).borrow_mut();
+        match key {
+            SliceOrIndex::Index(index) => {
+                let index = normalize_index(py, index, items.len())?;
+                items.remove(index);
+            }
+            SliceOrIndex::Slice(slice) => {
+                let indices = slice.indices(py, items.len())?;
+                let mut removed: Vec<usize> = (0..indices.slicelength)
+                    .map(|i| indices.index(i) as usize)
+                    .collect();
+                removed.sort();
+                for &index in removed.iter().rev() {
+                    items.remove(index);
+                }
+            }
+        }
+        Ok(())
+    }
+});
+
+fn normalize_index(py: Python, index: isize, len: usize) -> PyResult<usize> {
+    let index = if index < 0 { index + len as isize } else { index };
+    if index >= 0 && (index as usize) < len {
+        Ok(index as usize)
+    } else {
+        Err(PyErr::new::<exc::IndexError, NoArgs>(py, NoArgs))
+    }
+}
+
+#[test]
+fn sliceable_sequence() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = SliceableSequence::create_instance(py, RefCell::new((0..10).collect())).unwrap();
+    py_assert!(py, c, "c[3] == 3");
+    py_assert!(py, c, "c[-1] == 9");
+    py_assert!(py, c, "c[1:10:2] == [1, 3, 5, 7, 9]");
+    py_assert!(py, c, "c[::-3] == [9, 6, 3, 0]");
+    py_expect_exception!(py, c, "c['abc']", TypeError);
+    py_expect_exception!(py, c, "c[10]", IndexError);
+}
+
+#[test]
+fn sliceable_sequence_assignment() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = SliceableSequence::create_instance(py, RefCell::new((0..6).collect())).unwrap();
+    py_run!(py, c, "c[1] = 10");
+    py_run!(py, c, "c[-1] = 50");
+    assert_eq!(*c.items(py).borrow(), [0, 10, 2, 3, 4, 50]);
+    py_run!(py, c, "c[1:3] = [7, 8, 9]");
+    assert_eq!(*c.items(py).borrow(), [0, 7, 8, 9, 3, 4, 50]);
+    py_run!(py, c, "c[5:] = []");
+    assert_eq!(*c.items(py).borrow(), [0, 7, 8, 9, 3]);
+    py_expect_exception!(py, c, "c[::2] = [1, 2, 3]", ValueError);
+    py_expect_exception!(py, c, "c[5] = 1", IndexError);
+    py_expect_exception!(py, c, "c[1:3] = 'ab'", TypeError);
+}
+
+#[test]
+fn sliceable_sequence_deletion() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = SliceableSequence::create_instance(py, RefCell::new((0..10).collect())).unwrap();
+    py_run!(py, c, "del c[0]");
+    py_run!(py, c, "del c[-1]");
+    assert_eq!(*c.items(py).borrow(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    py_run!(py, c, "del c[1:3]");
+    assert_eq!(*c.items(py).borrow(), [1, 4, 5, 6, 7, 8]);
+    py_run!(py, c, "del c[::-2]");
+    assert_eq!(*c.items(py).borrow(), [1, 5, 7]);
+    py_expect_exception!(py, c, "del c[3]", IndexError);
+    py_assert!(py, c, "len(c) == 3");
+}
+
 
 py_class!(class Callable |py| {
     def __call__(&self, arg: i32) -> PyResult<i32> {
@@ -486,6 +648,62 @@ fn callable() {
     py_assert!(py, nc, "not callable(nc)");
 }
 
+py_class!(class ArgumentErrors |py| {
+    def __new__(_cls, x: i32) -> PyResult<ArgumentErrors> {
+        ArgumentErrors::create_instance(py)
+    }
+
+    def method(&self, a: i32) -> PyResult<i32> {
+        Ok(a)
+    }
+
+    @classmethod
+    def class_method(cls, a: i32) -> PyResult<i32> {
+        Ok(a)
+    }
+
+    @staticmethod
+    def static_method(a: i32) -> PyResult<i32> {
+        Ok(a)
+    }
+
+    def __call__(&self, a: i32) -> PyResult<i32> {
+        Ok(a)
+    }
+});
+
+#[test]
+fn argument_errors() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let cls = py.get_type::<ArgumentErrors>();
+    py_expect_type_error!(py, cls, "cls()",
+        "ArgumentErrors.__new__() missing 1 required positional argument: 'x'");
+    py_expect_type_error!(py, cls, "cls('abc')",
+        "ArgumentErrors.__new__() argument 'x': expected int, got str");
+    py_expect_type_error!(py, cls, "cls.class_method()",
+        "ArgumentErrors.class_method() missing 1 required positional argument: 'a'");
+    py_expect_type_error!(py, cls, "cls.class_method('abc')",
+        "ArgumentErrors.class_method() argument 'a': expected int, got str");
+    py_expect_type_error!(py, cls, "cls.static_method(1, 2)",
+        "ArgumentErrors.static_method() takes 1 positional argument but 2 were given");
+    py_expect_type_error!(py, cls, "cls.static_method('abc')",
+        "ArgumentErrors.static_method() argument 'a': expected int, got str");
+
+    let c = ArgumentErrors::create_instance(py).unwrap();
+    py_expect_type_error!(py, c, "c.method()",
+        "ArgumentErrors.method() missing 1 required positional argument: 'a'");
+    py_expect_type_error!(py, c, "c.method(b=1)",
+        "ArgumentErrors.method() got an unexpected keyword argument 'b'");
+    py_expect_type_error!(py, c, "c.method('abc')",
+        "ArgumentErrors.method() argument 'a': expected int, got str");
+    py_expect_type_error!(py, c, "c()",
+        "ArgumentErrors.__call__() missing 1 required positional argument: 'a'");
+    py_expect_type_error!(py, c, "c('abc')",
+        "ArgumentErrors.__call__() argument 'a': expected int, got str");
+}
+
 py_class!(class SetItem |py| {
     data key: Cell<i32>;
     data val: Cell<i32>;
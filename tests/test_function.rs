@@ -1,6 +1,7 @@
 #[macro_use] extern crate cpython;
 
-use cpython::{PyResult, Python, NoArgs, ObjectProtocol, PyDict};
+use cpython::{PyResult, Python, NoArgs, ObjectProtocol, PyDict, PythonObject, ToPyObject, py_fn_closure};
+use std::sync::Arc;
 use std::sync::atomic;
 use std::sync::atomic::Ordering::Relaxed;
 
@@ -65,6 +66,146 @@ fn inline_two_args() {
     assert_eq!(obj.call(py, (6, 7), None).unwrap().extract::<i32>(py).unwrap(), 42);
 }
 
+#[test]
+fn keyword_args() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(a: i32, b: i32 = 5) -> PyResult<i32> {
+        drop(py);
+        Ok(a * 10 + b)
+    });
+
+    assert_eq!(obj.call(py, (1,), None).unwrap().extract::<i32>(py).unwrap(), 15);
+    assert_eq!(obj.call(py, (1, 2), None).unwrap().extract::<i32>(py).unwrap(), 12);
+    assert!(obj.call(py, (1, 2, 3), None).is_err());
+
+    let dict = PyDict::new(py);
+    dict.set_item(py, "b", 3).unwrap();
+    assert_eq!(obj.call(py, (1,), Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 13);
+    assert!(obj.call(py, NoArgs, Some(&dict)).is_err());
+    assert!(obj.call(py, (1, 2), Some(&dict)).is_err());
+    dict.set_item(py, "a", 4).unwrap();
+    assert_eq!(obj.call(py, NoArgs, Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 43);
+    dict.set_item(py, "c", 0).unwrap();
+    assert!(obj.call(py, NoArgs, Some(&dict)).is_err());
+}
+
+#[test]
+fn calling_convention() {
+    use cpython::_detail::ffi;
+
+    fn f(_py: Python) -> PyResult<usize> {
+        Ok(0)
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let no_args = py_fn!(py, f());
+    let flags = unsafe { ffi::PyCFunction_GetFlags(no_args.as_ptr()) };
+    assert_eq!(flags, ffi::METH_NOARGS);
+
+    let two_args = py_fn!(py, g(a: i32, b: i32) -> PyResult<i32> {
+        drop(py);
+        Ok(a + b)
+    });
+    let flags = unsafe { ffi::PyCFunction_GetFlags(two_args.as_ptr()) };
+    #[cfg(Py_3_7)]
+    assert_eq!(flags, ffi::METH_FASTCALL | ffi::METH_KEYWORDS);
+    #[cfg(not(Py_3_7))]
+    assert_eq!(flags, ffi::METH_VARARGS | ffi::METH_KEYWORDS);
+}
+
+#[test]
+fn positional_and_keyword_only() {
+    use cpython::_detail::ffi;
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let one_arg = py_fn!(py, f(a: i32, /) -> PyResult<i32> {
+        drop(py);
+        Ok(a * 2)
+    });
+    let flags = unsafe { ffi::PyCFunction_GetFlags(one_arg.as_ptr()) };
+    assert_eq!(flags, ffi::METH_O);
+    assert_eq!(one_arg.call(py, (21,), None).unwrap().extract::<i32>(py).unwrap(), 42);
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(py, "a", 21).unwrap();
+    assert!(one_arg.call(py, NoArgs, Some(&kwargs)).is_err());
+
+    let obj = py_fn!(py, g(a: i32, /, b: i32, *, c: i32 = 0) -> PyResult<i32> {
+        drop(py);
+        Ok(a * 100 + b * 10 + c)
+    });
+    assert_eq!(obj.call(py, (1, 2), None).unwrap().extract::<i32>(py).unwrap(), 120);
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(py, "b", 2).unwrap();
+    kwargs.set_item(py, "c", 3).unwrap();
+    assert_eq!(obj.call(py, (1,), Some(&kwargs)).unwrap().extract::<i32>(py).unwrap(), 123);
+    assert!(obj.call(py, (1, 2, 3), None).is_err());
+}
+
+#[test]
+fn argument_conversion_error() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let obj = py_fn!(py, f(count: i32) -> PyResult<i32> {
+        drop(py);
+        Ok(count)
+    });
+    let d = PyDict::new(py);
+    d.set_item(py, "f", obj).unwrap();
+    py.run("try:\n  f('abc')\nexcept TypeError as e: msg = str(e)", None, Some(&d)).unwrap();
+    assert_eq!(d.get_item(py, "msg").unwrap().extract::<String>(py).unwrap(),
+        "f() argument 'count': expected int, got str");
+}
+
+#[test]
+fn closure() {
+    struct DropFlag(Arc<atomic::AtomicBool>);
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Relaxed);
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let dropped = Arc::new(atomic::AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let obj = py_fn_closure(py, "closure", move |py, args, kwargs| {
+        let _ = &flag;
+        Ok((args.len(py), kwargs.map_or(0, |kwargs| kwargs.len(py))).to_py_object(py).into_object())
+    });
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(py, "a", 1).unwrap();
+    assert_eq!(obj.call(py, (1, 2), Some(&kwargs)).unwrap().extract::<(usize, usize)>(py).unwrap(), (2, 1));
+    assert_eq!(obj.getattr(py, "__name__").unwrap().extract::<String>(py).unwrap(), "closure");
+    let repr = obj.repr(py).unwrap().to_string_lossy(py).into_owned();
+    assert!(!repr.contains("PyCapsule"), repr);
+    #[cfg(feature="python3-sys")]
+    assert_eq!(repr, "<built-in function closure>");
+    assert!(!dropped.load(Relaxed));
+    drop(obj);
+    assert!(dropped.load(Relaxed));
+}
+
+#[test]
+fn closure_with_typed_params() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let offset = 10;
+    let obj = py_closure!(py, add_offset(x: i32, scale: i32 = 1) -> PyResult<i32> {
+        Ok(x * scale + offset)
+    });
+    assert_eq!(obj.call(py, (32,), None).unwrap().extract::<i32>(py).unwrap(), 42);
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(py, "scale", 2).unwrap();
+    assert_eq!(obj.call(py, (16,), Some(&kwargs)).unwrap().extract::<i32>(py).unwrap(), 42);
+    assert!(obj.call(py, ("abc",), None).is_err());
+}
+
 /* TODO: reimplement flexible sig support
 #[test]
 fn flexible_sig() {